@@ -1,15 +1,23 @@
 use std::ops::Range;
+use std::sync::Arc;
 
-use crossterm::style::ContentStyle;
+use crossterm::style::{Attribute, ContentStyle};
 
 use crate::{Pos, Size, Style, Styled, WidthDb};
 
+/// A single cell of a [`Buffer`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Cell {
+    /// The grapheme displayed in the cell.
     pub content: Box<str>,
     pub style: ContentStyle,
+    /// Width of the grapheme. Wide graphemes span multiple cells, each of
+    /// which contains the entire grapheme.
     pub width: u8,
+    /// Offset of the cell from the first cell of its grapheme.
     pub offset: u8,
+    /// Target of the OSC 8 hyperlink the cell is part of.
+    pub link: Option<Arc<str>>,
 }
 
 impl Default for Cell {
@@ -19,6 +27,7 @@ impl Default for Cell {
             style: ContentStyle::default(),
             width: 1,
             offset: 0,
+            link: None,
         }
     }
 }
@@ -90,6 +99,7 @@ impl StackFrame {
     }
 }
 
+/// A grid of cells, for example the contents of the screen.
 #[derive(Debug, Default, Clone)]
 pub struct Buffer {
     size: Size,
@@ -103,6 +113,18 @@ pub struct Buffer {
     /// by the position of the topmost stack element. No characters can be
     /// placed outside the area described by the topmost stack element.
     stack: Vec<StackFrame>,
+
+    /// A stack of hyperlink targets.
+    ///
+    /// All cells written while the stack is nonempty become part of the
+    /// hyperlink of the topmost stack element.
+    links: Vec<Arc<str>>,
+
+    /// Which rows have been written to since the buffer was last reset.
+    ///
+    /// Rows that were not written to only contain empty cells, so they don't
+    /// need to be compared when looking for differences between two buffers.
+    dirty_rows: Vec<bool>,
 }
 
 impl Buffer {
@@ -153,14 +175,26 @@ impl Buffer {
         })
     }
 
-    pub fn push(&mut self, pos: Pos, size: Size) {
+    pub(crate) fn push(&mut self, pos: Pos, size: Size) {
         self.stack.push(self.current_frame().then(pos, size));
     }
 
-    pub fn pop(&mut self) {
+    pub(crate) fn pop(&mut self) {
         self.stack.pop();
     }
 
+    pub(crate) fn push_link(&mut self, url: &str) {
+        self.links.push(url.into());
+    }
+
+    pub(crate) fn pop_link(&mut self) {
+        self.links.pop();
+    }
+
+    pub(crate) fn link(&self) -> Option<&str> {
+        self.links.last().map(|l| l as &str)
+    }
+
     /// Size of the current drawable area, respecting the stack.
     pub fn size(&self) -> Size {
         self.current_frame().size
@@ -170,15 +204,29 @@ impl Buffer {
         self.cursor.map(|p| self.current_frame().global_to_local(p))
     }
 
-    pub fn set_cursor(&mut self, pos: Option<Pos>) {
+    pub(crate) fn set_cursor(&mut self, pos: Option<Pos>) {
         self.cursor = pos.map(|p| self.current_frame().local_to_global(p));
     }
 
+    /// Position and size of the area that can currently be drawn to, in global
+    /// coordinates.
+    pub(crate) fn drawable_area(&self) -> Option<(Pos, Size)> {
+        self.current_frame().drawable_area
+    }
+
+    pub(crate) fn local_to_global(&self, pos: Pos) -> Pos {
+        self.current_frame().local_to_global(pos)
+    }
+
+    pub(crate) fn global_to_local(&self, pos: Pos) -> Pos {
+        self.current_frame().global_to_local(pos)
+    }
+
     /// Resize the buffer and reset its contents.
     ///
     /// The buffer's contents are reset even if the buffer is already the
-    /// correct size. The stack is reset as well.
-    pub fn resize(&mut self, size: Size) {
+    /// correct size. The stack and link stack are reset as well.
+    pub(crate) fn resize(&mut self, size: Size) {
         if size == self.size {
             self.data.fill_with(Cell::default);
         } else {
@@ -193,13 +241,17 @@ impl Buffer {
 
         self.cursor = None;
 
+        self.dirty_rows.clear();
+        self.dirty_rows.resize(size.height.into(), false);
+
         self.stack.clear();
+        self.links.clear();
     }
 
     /// Reset the contents and stack of the buffer.
     ///
     /// `buf.reset()` is equivalent to `buf.resize(buf.size())`.
-    pub fn reset(&mut self) {
+    pub(crate) fn reset(&mut self) {
         self.resize(self.size);
     }
 
@@ -229,7 +281,7 @@ impl Buffer {
     ///
     /// The initial x position is considered the first column for tab width
     /// calculations.
-    pub fn write(&mut self, widthdb: &mut WidthDb, pos: Pos, styled: &Styled) {
+    pub(crate) fn write(&mut self, widthdb: &mut WidthDb, pos: Pos, styled: &Styled) {
         let frame = self.current_frame();
         let (xrange, yrange) = match frame.legal_ranges() {
             Some(ranges) => ranges,
@@ -242,16 +294,19 @@ impl Buffer {
         let y = pos.y as u16;
 
         let mut col: usize = 0;
-        for (_, style, grapheme) in styled.styled_grapheme_indices() {
+        for (_, style, link, grapheme) in styled.linked_grapheme_indices() {
+            // Links of the text take precedence over links of the frame
+            let link = link.or(self.links.last()).cloned();
             let x = pos.x + col as i32;
             let width = widthdb.grapheme_width(grapheme, col);
             col += width as usize;
             if grapheme == "\t" {
                 for dx in 0..width {
-                    self.write_grapheme(&xrange, x + dx as i32, y, 1, " ", style);
+                    let x = x + dx as i32;
+                    self.write_grapheme(&xrange, x, y, 1, " ", style, link.as_ref());
                 }
             } else if width > 0 {
-                self.write_grapheme(&xrange, x, y, width, grapheme, style);
+                self.write_grapheme(&xrange, x, y, width, grapheme, style, link.as_ref());
             }
         }
     }
@@ -259,6 +314,7 @@ impl Buffer {
     /// Write a single grapheme to the buffer, respecting its width.
     ///
     /// Assumes that `pos.y` is in range.
+    #[allow(clippy::too_many_arguments)]
     fn write_grapheme(
         &mut self,
         xrange: &Range<i32>,
@@ -267,6 +323,7 @@ impl Buffer {
         width: u8,
         grapheme: &str,
         style: Style,
+        link: Option<&Arc<str>>,
     ) {
         let min_x = xrange.start;
         let max_x = xrange.end - 1; // Last possible cell
@@ -278,6 +335,8 @@ impl Buffer {
             return; // Not visible
         }
 
+        self.dirty_rows[y as usize] = true;
+
         if start_x >= min_x && end_x <= max_x {
             // Fully visible, write actual grapheme
             let base_style = self.at(start_x as u16, y).style;
@@ -289,17 +348,19 @@ impl Buffer {
                     style: style.cover(base_style),
                     width,
                     offset,
+                    link: link.cloned(),
                 };
             }
         } else {
             // Partially visible, write empty cells with correct style
-            let start_x = start_x.max(0) as u16;
+            let start_x = start_x.max(min_x) as u16;
             let end_x = end_x.min(max_x) as u16;
             for x in start_x..=end_x {
                 let base_style = self.at(x, y).style;
                 self.erase(x, y);
                 *self.at_mut(x, y) = Cell {
                     style: style.cover(base_style),
+                    link: link.cloned(),
                     ..Default::default()
                 };
             }
@@ -313,19 +374,112 @@ impl Buffer {
         }
     }
 
+    /// Reset all cells of a row, for example because the row was cleared on
+    /// the screen. The row must be inside the buffer.
+    ///
+    /// Ignores the stack.
+    pub(crate) fn clear_row(&mut self, y: u16) {
+        for x in 0..self.size.width {
+            *self.at_mut(x, y) = Cell::default();
+        }
+        self.dirty_rows[y as usize] = false;
+    }
+
+    /// Display the cursor by reversing the colors of the grapheme under it.
+    ///
+    /// Does nothing if the cursor is hidden or outside the buffer.
+    pub(crate) fn draw_soft_cursor(&mut self) {
+        let Some(pos) = self.cursor else { return };
+        let x_in_bounds = 0 <= pos.x && pos.x < self.size.width as i32;
+        let y_in_bounds = 0 <= pos.y && pos.y < self.size.height as i32;
+        if !x_in_bounds || !y_in_bounds {
+            return;
+        }
+        let (x, y) = (pos.x as u16, pos.y as u16);
+
+        let cell = self.at(x, y);
+        let start = x - cell.offset as u16;
+        let end = start + cell.width as u16;
+        for x in start..end {
+            self.at_mut(x, y)
+                .style
+                .attributes
+                .toggle(Attribute::Reverse);
+        }
+        self.dirty_rows[y as usize] = true;
+    }
+
+    /// Iterate over the cells of the buffer row by row, along with their
+    /// coordinates.
+    ///
+    /// Cells with a nonzero offset are skipped, so each grapheme is only
+    /// returned once.
     pub fn cells(&self) -> Cells<'_> {
         Cells {
             buffer: self,
             x: 0,
             y: 0,
+            end_y: self.size.height,
+        }
+    }
+
+    /// Like [`Self::cells`], but only iterates over a single row.
+    pub(crate) fn row_cells(&self, y: u16) -> Cells<'_> {
+        Cells {
+            buffer: self,
+            x: 0,
+            y,
+            end_y: y.saturating_add(1).min(self.size.height),
+        }
+    }
+
+    /// Whether the row may contain anything other than empty cells, i.e.
+    /// whether it has been written to since the buffer was last reset.
+    pub(crate) fn is_row_dirty(&self, y: u16) -> bool {
+        self.dirty_rows.get(y as usize).copied().unwrap_or(false)
+    }
+
+    /// The buffer's contents as plain text, with rows separated by newlines.
+    pub fn to_plain_string(&self) -> String {
+        let mut result = String::new();
+        for (x, y, cell) in self.cells() {
+            if x == 0 && y > 0 {
+                result.push('\n');
+            }
+            result.push_str(&cell.content);
+        }
+        result
+    }
+
+    /// The buffer's contents as styled text, with rows separated by newlines.
+    ///
+    /// Styles are opaque and hyperlinks are preserved.
+    pub fn to_styled(&self) -> Styled {
+        let mut result = Styled::default();
+        for (x, y, cell) in self.cells() {
+            if x == 0 && y > 0 {
+                result.push_plain("\n");
+            }
+            let style = Style {
+                content_style: cell.style,
+                ..Style::new().opaque()
+            };
+            result = match &cell.link {
+                Some(link) => result.then_link(&cell.content, link, style),
+                None => result.then(&cell.content, style),
+            };
         }
+        result
     }
 }
 
+/// Iterator over the cells of a [`Buffer`], see [`Buffer::cells`].
 pub struct Cells<'a> {
     buffer: &'a Buffer,
     x: u16,
     y: u16,
+    /// The first row that is not part of the iteration any more.
+    end_y: u16,
 }
 
 impl<'a> Iterator for Cells<'a> {
@@ -335,7 +489,7 @@ impl<'a> Iterator for Cells<'a> {
         if self.x >= self.buffer.size.width {
             return None;
         }
-        if self.y >= self.buffer.size.height {
+        if self.y >= self.end_y {
             return None;
         }
 
@@ -0,0 +1,100 @@
+//! Clipboard backends for copying and pasting text.
+
+/// A place to copy text to and paste text from.
+pub trait Clipboard {
+    /// The current contents of the clipboard, if any.
+    fn get(&mut self) -> Option<String>;
+
+    /// Replace the contents of the clipboard.
+    fn set(&mut self, text: String);
+}
+
+/// A clipboard that only lives inside the current process.
+#[derive(Debug, Default, Clone)]
+pub struct LocalClipboard {
+    pub contents: Option<String>,
+}
+
+impl LocalClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Clipboard for LocalClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.contents.clone()
+    }
+
+    fn set(&mut self, text: String) {
+        self.contents = Some(text);
+    }
+}
+
+/// A clipboard that copies to the system clipboard via the terminal.
+///
+/// Copied text is sent to the terminal using OSC 52 when
+/// [`crate::Terminal::sync_clipboard`] is called. Since most terminals don't
+/// allow reading the system clipboard, pasting only returns text copied
+/// through this clipboard. Text pasted by the user via the terminal arrives
+/// as a regular paste event instead.
+#[derive(Debug, Default, Clone)]
+pub struct Osc52Clipboard {
+    contents: Option<String>,
+    /// Whether the contents have not yet been sent to the terminal.
+    pending: bool,
+}
+
+impl Osc52Clipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The contents that have not yet been sent to the terminal, if any.
+    ///
+    /// Marks the contents as sent.
+    pub fn take_pending(&mut self) -> Option<&str> {
+        if !self.pending {
+            return None;
+        }
+        self.pending = false;
+        self.contents.as_deref()
+    }
+}
+
+impl Clipboard for Osc52Clipboard {
+    fn get(&mut self) -> Option<String> {
+        self.contents.clone()
+    }
+
+    fn set(&mut self, text: String) {
+        self.contents = Some(text);
+        self.pending = true;
+    }
+}
+
+/// The system clipboard, accessed via the [`arboard`] crate.
+#[cfg(feature = "arboard")]
+pub struct ArboardClipboard {
+    clipboard: arboard::Clipboard,
+}
+
+#[cfg(feature = "arboard")]
+impl ArboardClipboard {
+    pub fn new() -> Result<Self, arboard::Error> {
+        Ok(Self {
+            clipboard: arboard::Clipboard::new()?,
+        })
+    }
+}
+
+#[cfg(feature = "arboard")]
+impl Clipboard for ArboardClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.clipboard.get_text().ok()
+    }
+
+    fn set(&mut self, text: String) {
+        let _ = self.clipboard.set_text(text);
+    }
+}
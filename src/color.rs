@@ -0,0 +1,185 @@
+//! Converting colors for terminals with limited color support.
+
+use std::env;
+
+use crossterm::style::{Color, ContentStyle};
+
+/// The colors a terminal is able to display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// No colors and no attributes like bold or underlined, only plain text.
+    NoColor,
+    /// The 16 basic ANSI colors.
+    Ansi16,
+    /// The 256 colors of the xterm palette.
+    Ansi256,
+    /// Arbitrary RGB colors.
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Guess the color depth of the terminal from the `NO_COLOR`, `COLORTERM`
+    /// and `TERM` environment variables.
+    ///
+    /// If `NO_COLOR` is set to a non-empty value (see <https://no-color.org/>)
+    /// or `TERM` is `dumb`, no colors are used at all.
+    pub fn detect() -> Self {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let term = env::var("TERM").unwrap_or_default();
+        if no_color || term == "dumb" {
+            return Self::NoColor;
+        }
+
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Self::TrueColor;
+        }
+
+        if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Convert a color to the closest color of this depth.
+    ///
+    /// With [`Self::NoColor`], all colors are converted to [`Color::Reset`].
+    pub fn convert(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::NoColor, _) => Color::Reset,
+            (Self::TrueColor, _) => color,
+            (Self::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(nearest_256((r, g, b))),
+            (Self::Ansi16, Color::Rgb { r, g, b }) => nearest_16((r, g, b)),
+            (Self::Ansi16, Color::AnsiValue(value)) => nearest_16(ansi_to_rgb(value)),
+            _ => color,
+        }
+    }
+
+    /// Convert all colors of a style like [`Self::convert`].
+    ///
+    /// With [`Self::NoColor`], attributes are removed as well.
+    pub(crate) fn convert_style(self, style: ContentStyle) -> ContentStyle {
+        if self == Self::NoColor {
+            return ContentStyle::default();
+        }
+
+        ContentStyle {
+            foreground_color: style.foreground_color.map(|c| self.convert(c)),
+            background_color: style.background_color.map(|c| self.convert(c)),
+            underline_color: style.underline_color.map(|c| self.convert(c)),
+            attributes: style.attributes,
+        }
+    }
+}
+
+/// The 16 basic colors in the order of their ANSI values, along with typical
+/// RGB values.
+const BASIC: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Intensities of the 6x6x6 color cube of the 256 color palette.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let dr = a.0.abs_diff(b.0) as u32;
+    let dg = a.1.abs_diff(b.1) as u32;
+    let db = a.2.abs_diff(b.2) as u32;
+    dr * dr + dg * dg + db * db
+}
+
+fn ansi_to_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => BASIC[value as usize].1,
+        16..=231 => {
+            let i = value - 16;
+            (
+                CUBE[(i / 36) as usize],
+                CUBE[(i / 6 % 6) as usize],
+                CUBE[(i % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let v = 8 + (value - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+/// The typical RGB value of a color, or [`None`] for [`Color::Reset`].
+pub(crate) fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb { r, g, b } => Some((r, g, b)),
+        Color::AnsiValue(value) => Some(ansi_to_rgb(value)),
+        _ => BASIC.iter().find(|(c, _)| *c == color).map(|(_, rgb)| *rgb),
+    }
+}
+
+/// Find the color in the terminal's reply to an OSC 10 or 11 query, for
+/// example `ESC ] 11 ; rgb:ffff/ffff/ffff ESC \`.
+pub(crate) fn parse_color_reply(reply: &str, code: u8) -> Option<(u8, u8, u8)> {
+    let prefix = format!("\x1b]{code};rgb:");
+    let start = reply.find(&prefix)? + prefix.len();
+    let rest = &reply[start..];
+    let end = rest.find(['\x07', '\x1b'])?;
+
+    let mut components = rest[..end].split('/').map(parse_color_component);
+    let r = components.next()??;
+    let g = components.next()??;
+    let b = components.next()??;
+    if components.next().is_some() {
+        return None;
+    }
+    Some((r, g, b))
+}
+
+/// Parse a color component with 1 to 4 hex digits, scaling it to a single
+/// byte.
+fn parse_color_component(hex: &str) -> Option<u8> {
+    if hex.is_empty() || hex.len() > 4 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let max = (1 << (4 * hex.len())) - 1;
+    Some(((value * 255 + max / 2) / max) as u8)
+}
+
+/// Whether a color is closer to black than to white, judging by its relative
+/// luminance.
+pub(crate) fn is_dark((r, g, b): (u8, u8, u8)) -> bool {
+    let luminance = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+    luminance < 128.0
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    BASIC
+        .iter()
+        .min_by_key(|(_, basic)| distance(rgb, *basic))
+        .map(|(color, _)| *color)
+        .expect("palette is not empty")
+}
+
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    // The basic colors are skipped since their actual values vary a lot
+    // between terminals.
+    (16..=255)
+        .min_by_key(|value| distance(rgb, ansi_to_rgb(*value)))
+        .expect("palette is not empty")
+}
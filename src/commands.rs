@@ -0,0 +1,214 @@
+//! Terminal commands not provided by crossterm.
+
+use std::fmt;
+#[cfg(windows)]
+use std::io;
+
+use crossterm::Command;
+
+use crate::NotificationProtocol;
+
+/// Start or end an OSC 8 hyperlink.
+///
+/// All text printed after a hyperlink was started is part of the hyperlink
+/// until it is ended again.
+pub(crate) struct SetHyperlink<'a>(pub Option<&'a str>);
+
+impl Command for SetHyperlink<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b]8;;")?;
+        // Control characters could end the escape sequence prematurely
+        let url = self.0.unwrap_or("");
+        for c in url.chars().filter(|c| !c.is_control()) {
+            f.write_char(c)?;
+        }
+        write!(f, "\x1b\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Set the system clipboard using OSC 52.
+///
+/// Not all terminals support this, and some only support it after the user
+/// opted in.
+pub(crate) struct SetClipboard<'a>(pub &'a str);
+
+impl Command for SetClipboard<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b]52;c;")?;
+        write_base64(f, self.0.as_bytes())?;
+        write!(f, "\x1b\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Save the current window title on the terminal's title stack.
+pub(crate) struct PushTitle;
+
+impl Command for PushTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b[22;0t")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Restore the window title most recently saved using [`PushTitle`].
+pub(crate) struct PopTitle;
+
+impl Command for PopTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b[23;0t")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Set the cursor color using OSC 12, or reset it to the default using OSC
+/// 112.
+pub(crate) struct SetCursorColor(pub Option<(u8, u8, u8)>);
+
+impl Command for SetCursorColor {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self.0 {
+            Some((r, g, b)) => write!(f, "\x1b]12;#{r:02x}{g:02x}{b:02x}\x1b\\"),
+            None => write!(f, "\x1b]112\x1b\\"),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Ask the terminal for one of its dynamic colors, for example the default
+/// foreground (10) or background (11) color.
+pub(crate) struct QueryColor(pub u8);
+
+impl Command for QueryColor {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b]{};?\x1b\\", self.0)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Enable or disable mode 2027, in which terminals treat grapheme clusters as
+/// a single unit when determining their width.
+pub(crate) struct SetGraphemeClusterMode(pub bool);
+
+impl Command for SetGraphemeClusterMode {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let action = if self.0 { 'h' } else { 'l' };
+        write!(f, "\x1b[?2027{action}")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Ring the terminal bell.
+pub(crate) struct RingBell;
+
+impl Command for RingBell {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_char('\x07')
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Show a desktop notification using OSC 9 or OSC 777.
+pub(crate) struct Notify<'a> {
+    pub protocol: NotificationProtocol,
+    pub title: &'a str,
+    pub body: &'a str,
+}
+
+impl Command for Notify<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self.protocol {
+            NotificationProtocol::Osc9 => {
+                // OSC 9 has no separate title
+                write!(f, "\x1b]9;")?;
+                write_notification_text(f, self.title, &[])?;
+                if !self.title.is_empty() && !self.body.is_empty() {
+                    write!(f, ": ")?;
+                }
+                write_notification_text(f, self.body, &[])?;
+            }
+            NotificationProtocol::Osc777 => {
+                write!(f, "\x1b]777;notify;")?;
+                write_notification_text(f, self.title, &[';'])?;
+                f.write_char(';')?;
+                write_notification_text(f, self.body, &[])?;
+            }
+        }
+        write!(f, "\x1b\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(()) // Not supported
+    }
+}
+
+/// Write text without control characters, which could end the escape sequence
+/// prematurely, and without the specified separators.
+fn write_notification_text(
+    f: &mut impl fmt::Write,
+    text: &str,
+    separators: &[char],
+) -> fmt::Result {
+    for c in text.chars() {
+        if !c.is_control() && !separators.contains(&c) {
+            f.write_char(c)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_base64(f: &mut impl fmt::Write, bytes: &[u8]) -> fmt::Result {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let sextet = (n >> (18 - 6 * i)) & 0x3f;
+                f.write_char(ALPHABET[sextet as usize] as char)?;
+            } else {
+                f.write_char('=')?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! Errors that can occur when setting up a terminal.
+
+use std::{error, fmt, io};
+
+/// An error that occurred while creating a [`crate::Terminal`].
+///
+/// Applications can use this to fall back to a non-interactive mode if no
+/// usable terminal is available.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The output is not a terminal, for example because it is redirected to a
+    /// file or piped into another program.
+    NotATty,
+    /// Communicating with the terminal failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotATty => write!(f, "output is not a terminal"),
+            Self::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            err => Self::other(err),
+        }
+    }
+}
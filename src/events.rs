@@ -0,0 +1,196 @@
+//! Receiving terminal events asynchronously.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crossterm::event::EventStream;
+use futures_core::Stream;
+use tokio::time::{Instant, Interval, MissedTickBehavior, Sleep};
+
+use crate::{TimerId, Timers};
+
+/// An event produced by [`Events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// An event reported by the terminal, for example a key press, a mouse
+    /// event or a resize.
+    Terminal(crossterm::event::Event),
+    /// The tick interval (see [`Events::with_tick_rate`]) has elapsed.
+    Tick,
+    /// A timer (see [`Events::timers`]) has expired.
+    Timer(TimerId),
+}
+
+/// A [`Stream`] of terminal events, optionally combined with regular ticks and
+/// timers.
+///
+/// This allows async applications to wait for terminal events alongside other
+/// futures, for example using `tokio::select!`, without reading events on a
+/// separate blocking thread.
+///
+/// Must be used within a tokio runtime with the time driver enabled.
+///
+/// If a tick, timer or debounced resize is produced, the stream keeps waiting
+/// for terminal input in the background. Grapheme widths should not be
+/// measured until the stream has produced a terminal event again, since it
+/// may read the terminal's replies (see [`crate::Terminal::measure_widths`]).
+pub struct Events {
+    stream: EventStream,
+    tick: Option<Interval>,
+    timers: Timers,
+    /// Sleeps until the next timer expires. Created lazily because it must be
+    /// created within a tokio runtime.
+    sleep: Option<Pin<Box<Sleep>>>,
+    resize_debounce: Option<Duration>,
+    /// The most recent resize event that has not been produced yet.
+    pending_resize: Option<(u16, u16)>,
+    /// Sleeps until the pending resize event should be produced.
+    resize_sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Events {
+    pub fn new() -> Self {
+        Self {
+            stream: EventStream::new(),
+            tick: None,
+            timers: Timers::new(),
+            sleep: None,
+            resize_debounce: None,
+            pending_resize: None,
+            resize_sleep: None,
+        }
+    }
+
+    /// Set the interval in which [`Event::Tick`] is produced.
+    ///
+    /// For more details, see [`Self::with_tick_rate`].
+    pub fn set_tick_rate(&mut self, rate: Option<Duration>) {
+        self.tick = rate.map(|rate| {
+            let mut interval = tokio::time::interval(rate);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+    }
+
+    /// Produce an [`Event::Tick`] whenever the specified duration has elapsed.
+    ///
+    /// Ticks are useful for driving animations. If the application falls
+    /// behind, missed ticks are skipped instead of being produced in a burst.
+    /// No ticks are produced by default.
+    pub fn with_tick_rate(mut self, rate: Duration) -> Self {
+        self.set_tick_rate(Some(rate));
+        self
+    }
+
+    /// Set how long resize events are delayed.
+    ///
+    /// For more details, see [`Self::with_resize_debounce`].
+    pub fn set_resize_debounce(&mut self, delay: Option<Duration>) {
+        self.resize_debounce = delay;
+    }
+
+    /// Delay resize events until no further resize happened for the specified
+    /// duration.
+    ///
+    /// When the user resizes the terminal window, many resize events are
+    /// produced in quick succession. With a debounce, only the final size is
+    /// reported, so the application only needs to redraw once. Other events are
+    /// not delayed. Resize events are not delayed by default.
+    pub fn with_resize_debounce(mut self, delay: Duration) -> Self {
+        self.set_resize_debounce(Some(delay));
+        self
+    }
+
+    fn poll_terminal(&mut self, cx: &mut Context<'_>) -> Poll<Option<io::Result<Event>>> {
+        while let Poll::Ready(event) = Pin::new(&mut self.stream).poll_next(cx) {
+            let delay = match (&event, self.resize_debounce) {
+                (Some(Ok(crossterm::event::Event::Resize(w, h))), Some(delay)) => {
+                    self.pending_resize = Some((*w, *h));
+                    delay
+                }
+                _ => return Poll::Ready(event.map(|e| e.map(Event::Terminal))),
+            };
+
+            let deadline = Instant::now() + delay;
+            let sleep = self
+                .resize_sleep
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(deadline)));
+            sleep.as_mut().reset(deadline);
+        }
+
+        if self.pending_resize.is_some() {
+            if let Some(sleep) = &mut self.resize_sleep {
+                if sleep.as_mut().poll(cx).is_ready() {
+                    let (w, h) = self.pending_resize.take().unwrap();
+                    let event = crossterm::event::Event::Resize(w, h);
+                    return Poll::Ready(Some(Ok(Event::Terminal(event))));
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+
+    /// The timers whose expirations are produced as [`Event::Timer`].
+    pub fn timers(&mut self) -> &mut Timers {
+        &mut self.timers
+    }
+
+    fn poll_timers(&mut self, cx: &mut Context<'_>) -> Poll<TimerId> {
+        loop {
+            if let Some(id) = self.timers.pop_expired() {
+                return Poll::Ready(id);
+            }
+
+            let Some(deadline) = self.timers.next_deadline() else {
+                return Poll::Pending;
+            };
+            let deadline = Instant::from_std(deadline);
+            let sleep = self
+                .sleep
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(deadline)));
+            sleep.as_mut().reset(deadline);
+            if sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+
+    /// Wait for the next event.
+    ///
+    /// Returns [`None`] if no more terminal events can be read.
+    pub async fn next(&mut self) -> Option<io::Result<Event>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for Events {
+    type Item = io::Result<Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Poll::Ready(event) = self.poll_terminal(cx) {
+            return Poll::Ready(event);
+        }
+
+        if let Some(tick) = &mut self.tick {
+            if tick.poll_tick(cx).is_ready() {
+                return Poll::Ready(Some(Ok(Event::Tick)));
+            }
+        }
+
+        if let Poll::Ready(id) = self.poll_timers(cx) {
+            return Poll::Ready(Some(Ok(Event::Timer(id))));
+        }
+
+        Poll::Pending
+    }
+}
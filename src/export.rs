@@ -0,0 +1,233 @@
+//! Exporting buffers as HTML or SVG.
+
+use std::fmt::Write;
+
+use crossterm::style::{Attribute, ContentStyle};
+
+use crate::{color, Buffer};
+
+/// Colors used where a cell doesn't specify any.
+const DEFAULT_FG: (u8, u8, u8) = (204, 204, 204);
+const DEFAULT_BG: (u8, u8, u8) = (0, 0, 0);
+
+/// Size of a cell in SVG exports, in pixels.
+const SVG_FONT_SIZE: u32 = 15;
+const SVG_CELL_WIDTH: u32 = 9;
+const SVG_CELL_HEIGHT: u32 = 18;
+/// Distance from the top of a cell to the text's baseline, leaving room for
+/// descenders.
+const SVG_BASELINE: u32 = 14;
+
+/// Consecutive cells in a row with the same style and hyperlink.
+struct Run<'a> {
+    x: u16,
+    y: u16,
+    /// Width in cells.
+    width: u16,
+    text: String,
+    style: ContentStyle,
+    link: Option<&'a str>,
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Whether a hyperlink can be exported without allowing scripts to run, for
+/// example via `javascript:` links.
+fn safe_link(link: &str) -> bool {
+    let Some((scheme, _)) = link.split_once(':') else {
+        return false;
+    };
+    ["http", "https", "mailto", "file"]
+        .iter()
+        .any(|s| scheme.eq_ignore_ascii_case(s))
+}
+
+/// Foreground and background color of a style, respecting the reverse and
+/// hidden attributes.
+fn colors(style: &ContentStyle) -> ((u8, u8, u8), (u8, u8, u8)) {
+    let fg = style.foreground_color.and_then(color::to_rgb);
+    let bg = style.background_color.and_then(color::to_rgb);
+    let (fg, bg) = (fg.unwrap_or(DEFAULT_FG), bg.unwrap_or(DEFAULT_BG));
+    let (fg, bg) = if style.attributes.has(Attribute::Reverse) {
+        (bg, fg)
+    } else {
+        (fg, bg)
+    };
+    if style.attributes.has(Attribute::Hidden) {
+        (bg, bg)
+    } else {
+        (fg, bg)
+    }
+}
+
+/// CSS properties for the font of a style, shared by HTML and SVG.
+fn font_css(style: &ContentStyle) -> String {
+    let attrs = style.attributes;
+    let mut css = String::new();
+    if attrs.has(Attribute::Bold) {
+        css.push_str("font-weight:bold;");
+    }
+    if attrs.has(Attribute::Italic) {
+        css.push_str("font-style:italic;");
+    }
+    if attrs.has(Attribute::Dim) {
+        css.push_str("opacity:0.6;");
+    }
+    let underlined = attrs.has(Attribute::Underlined)
+        || attrs.has(Attribute::DoubleUnderlined)
+        || attrs.has(Attribute::Undercurled)
+        || attrs.has(Attribute::Underdotted)
+        || attrs.has(Attribute::Underdashed);
+    let crossed_out = attrs.has(Attribute::CrossedOut);
+    match (underlined, crossed_out) {
+        (true, true) => css.push_str("text-decoration:underline line-through;"),
+        (true, false) => css.push_str("text-decoration:underline;"),
+        (false, true) => css.push_str("text-decoration:line-through;"),
+        (false, false) => {}
+    }
+    css
+}
+
+impl Buffer {
+    fn runs(&self) -> Vec<Run<'_>> {
+        let mut runs: Vec<Run<'_>> = vec![];
+        for (x, y, cell) in self.cells() {
+            // Other links are exported as plain text
+            let link = cell.link.as_deref().filter(|l| safe_link(l));
+            if let Some(run) = runs.last_mut() {
+                if run.y == y && run.style == cell.style && run.link == link {
+                    run.width += cell.width as u16;
+                    run.text.push_str(&cell.content);
+                    continue;
+                }
+            }
+            runs.push(Run {
+                x,
+                y,
+                width: cell.width.into(),
+                text: cell.content.to_string(),
+                style: cell.style,
+                link,
+            });
+        }
+        runs
+    }
+
+    /// The buffer's contents as an HTML `<pre>` element with inline styles.
+    ///
+    /// Colors, attributes like bold or underlined, and hyperlinks are
+    /// preserved. Only `http`, `https`, `mailto` and `file` hyperlinks are
+    /// exported as links.
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<pre style=\"font-family:monospace;color:{};background-color:{}\">",
+            hex(DEFAULT_FG),
+            hex(DEFAULT_BG)
+        );
+        for run in self.runs() {
+            if run.x == 0 && run.y > 0 {
+                html.push('\n');
+            }
+            let (fg, bg) = colors(&run.style);
+            let css = format!(
+                "color:{};background-color:{};{}",
+                hex(fg),
+                hex(bg),
+                font_css(&run.style)
+            );
+            let text = escape(&run.text);
+            let _ = match run.link {
+                Some(link) => write!(
+                    html,
+                    "<a href=\"{}\" style=\"{css}\">{text}</a>",
+                    escape(link)
+                ),
+                None => write!(html, "<span style=\"{css}\">{text}</span>"),
+            };
+        }
+        html.push_str("</pre>");
+        html
+    }
+
+    /// The buffer's contents as an SVG image.
+    ///
+    /// Colors, attributes like bold or underlined, and hyperlinks are
+    /// preserved like in [`Self::to_html`]. Each cell is drawn at a fixed
+    /// position so the layout doesn't depend on the width of the viewer's
+    /// monospace font.
+    pub fn to_svg(&self) -> String {
+        let size = self.size();
+        let width = u32::from(size.width) * SVG_CELL_WIDTH;
+        let height = u32::from(size.height) * SVG_CELL_HEIGHT;
+
+        let mut svg = String::new();
+        let _ = write!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             font-family=\"monospace\" font-size=\"{SVG_FONT_SIZE}\">"
+        );
+        let _ = write!(
+            svg,
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+            hex(DEFAULT_BG)
+        );
+
+        let runs = self.runs();
+        for run in &runs {
+            let (_, bg) = colors(&run.style);
+            if bg != DEFAULT_BG {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{SVG_CELL_HEIGHT}\" fill=\"{}\"/>",
+                    u32::from(run.x) * SVG_CELL_WIDTH,
+                    u32::from(run.y) * SVG_CELL_HEIGHT,
+                    u32::from(run.width) * SVG_CELL_WIDTH,
+                    hex(bg)
+                );
+            }
+        }
+
+        for run in &runs {
+            if run.text.trim().is_empty() && run.link.is_none() {
+                continue;
+            }
+            let (fg, _) = colors(&run.style);
+            if let Some(link) = run.link {
+                let _ = write!(svg, "<a href=\"{}\">", escape(link));
+            }
+            let _ = write!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\" \
+                 xml:space=\"preserve\" fill=\"{}\" style=\"{}\">{}</text>",
+                u32::from(run.x) * SVG_CELL_WIDTH,
+                u32::from(run.y) * SVG_CELL_HEIGHT + SVG_BASELINE,
+                u32::from(run.width) * SVG_CELL_WIDTH,
+                hex(fg),
+                font_css(&run.style),
+                escape(&run.text)
+            );
+            if run.link.is_some() {
+                svg.push_str("</a>");
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+}
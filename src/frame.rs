@@ -1,13 +1,58 @@
 //! Rendering the next frame.
 
+use crossterm::style::Color;
+
 use crate::buffer::Buffer;
-use crate::{Pos, Size, Styled, WidthDb};
+use crate::{MouseArea, Pos, Size, Styled, WidthDb};
+
+/// The shape of the terminal cursor.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorStyle {
+    /// The shape configured by the user.
+    #[default]
+    Default,
+    BlinkingBlock,
+    SteadyBlock,
+    BlinkingUnderline,
+    SteadyUnderline,
+    BlinkingBar,
+    SteadyBar,
+}
+
+/// A desktop notification sent using [`Frame::notify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Notification {
+    pub title: String,
+    pub body: String,
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Frame {
     pub(crate) widthdb: WidthDb,
     pub(crate) buffer: Buffer,
     pub(crate) title: Option<String>,
+    pub(crate) mouse_areas: Vec<MouseArea>,
+    pub(crate) focused: bool,
+    pub(crate) cursor_style: CursorStyle,
+    pub(crate) cursor_color: Option<Color>,
+    pub(crate) bell: bool,
+    pub(crate) notifications: Vec<Notification>,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            widthdb: WidthDb::default(),
+            buffer: Buffer::default(),
+            title: None,
+            mouse_areas: vec![],
+            focused: true,
+            cursor_style: CursorStyle::Default,
+            cursor_color: None,
+            bell: false,
+            notifications: vec![],
+        }
+    }
 }
 
 impl Frame {
@@ -19,13 +64,60 @@ impl Frame {
         self.buffer.pop();
     }
 
+    /// Make all text written until the corresponding [`Self::pop_link`] part
+    /// of a hyperlink to `url`.
+    pub fn push_link(&mut self, url: &str) {
+        self.buffer.push_link(url);
+    }
+
+    pub fn pop_link(&mut self) {
+        self.buffer.pop_link();
+    }
+
+    pub fn link(&self) -> Option<&str> {
+        self.buffer.link()
+    }
+
     pub fn size(&self) -> Size {
         self.buffer.size()
     }
 
+    /// Convert a position relative to the current drawable area to a position
+    /// relative to the top left corner of the screen.
+    pub fn local_to_global(&self, pos: Pos) -> Pos {
+        self.buffer.local_to_global(pos)
+    }
+
+    /// Convert a position relative to the top left corner of the screen to a
+    /// position relative to the current drawable area.
+    pub fn global_to_local(&self, pos: Pos) -> Pos {
+        self.buffer.global_to_local(pos)
+    }
+
+    /// Remember the current drawable area under the name `id`.
+    ///
+    /// After the frame has been presented, mouse events can be translated
+    /// into coordinates relative to the area using
+    /// [`crate::Terminal::mouse_target`]. Areas registered later are
+    /// considered to be on top of areas registered earlier. Areas that are not
+    /// visible at all are ignored.
+    pub fn register_mouse_area<S: Into<String>>(&mut self, id: S) {
+        if let Some((pos, size)) = self.buffer.drawable_area() {
+            let origin = self.local_to_global(Pos::ZERO);
+            let area = MouseArea::new(id.into(), origin, pos, size);
+            self.mouse_areas.push(area);
+        }
+    }
+
     pub fn reset(&mut self) {
         self.buffer.reset();
         self.title = None;
+        self.mouse_areas.clear();
+        self.cursor_style = CursorStyle::Default;
+        self.cursor_color = None;
+        self.bell = false;
+        self.notifications.clear();
+        self.widthdb.start_frame();
     }
 
     pub fn cursor(&self) -> Option<Pos> {
@@ -44,10 +136,63 @@ impl Frame {
         self.set_cursor(None);
     }
 
+    pub fn cursor_style(&self) -> CursorStyle {
+        self.cursor_style
+    }
+
+    /// Set the shape of the cursor for this frame.
+    ///
+    /// Whether the shape is respected depends on the terminal.
+    pub fn set_cursor_style(&mut self, style: CursorStyle) {
+        self.cursor_style = style;
+    }
+
+    pub fn cursor_color(&self) -> Option<Color> {
+        self.cursor_color
+    }
+
+    /// Set the color of the cursor for this frame.
+    ///
+    /// If no color is set, the terminal's default cursor color is used.
+    /// Whether the color is respected depends on the terminal.
+    pub fn set_cursor_color(&mut self, color: Option<Color>) {
+        self.cursor_color = color;
+    }
+
+    /// Set the window title for this frame.
+    ///
+    /// If no title is set, the title stays as it was. The title is only sent to
+    /// the terminal when it changes.
     pub fn set_title(&mut self, title: Option<String>) {
         self.title = title;
     }
 
+    /// Ring the terminal bell once this frame is presented.
+    pub fn set_bell(&mut self, bell: bool) {
+        self.bell = bell;
+    }
+
+    /// Show a desktop notification once this frame is presented.
+    ///
+    /// Notifications are only shown if a protocol has been configured using
+    /// [`crate::Terminal::set_notification_protocol`] and the terminal
+    /// supports it.
+    pub fn notify<S1: Into<String>, S2: Into<String>>(&mut self, title: S1, body: S2) {
+        self.notifications.push(Notification {
+            title: title.into(),
+            body: body.into(),
+        });
+    }
+
+    /// Whether the terminal window currently has focus.
+    ///
+    /// Widgets may want to look different while the window is unfocused, for
+    /// example by dimming their cursor or pausing animations. For more
+    /// details, see [`crate::Terminal::focused`].
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
     pub fn widthdb(&mut self) -> &mut WidthDb {
         &mut self.widthdb
     }
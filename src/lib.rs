@@ -10,20 +10,43 @@
 #![warn(clippy::use_self)]
 
 mod buffer;
+mod clipboard;
+mod color;
+mod commands;
 mod coords;
+mod error;
+#[cfg(feature = "events")]
+pub mod events;
+#[cfg(feature = "export")]
+mod export;
 mod frame;
+mod mouse;
+mod output;
+mod query;
 mod style;
 mod styled;
 mod terminal;
+mod theme;
+mod timer;
 mod widget;
 pub mod widgets;
 mod widthdb;
 mod wrap;
 
+#[cfg(feature = "hyphenation")]
+pub use wrap::HyphenationLang;
+
+pub use buffer::*;
+pub use clipboard::*;
+pub use color::*;
 pub use coords::*;
+pub use error::*;
 pub use frame::*;
+pub use mouse::*;
 pub use style::*;
 pub use styled::*;
 pub use terminal::*;
+pub use theme::*;
+pub use timer::*;
 pub use widget::*;
 pub use widthdb::*;
@@ -0,0 +1,81 @@
+//! Translating mouse events into local coordinates.
+
+use crossterm::event::{MouseEvent, MouseEventKind};
+
+use crate::{Pos, Size};
+
+/// A region of the screen registered using [`crate::Frame::register_mouse_area`].
+///
+/// Mouse events use global coordinates. The area remembers where the region
+/// was drawn so events can be translated into the coordinates the widget used
+/// while drawing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MouseArea {
+    pub id: String,
+    /// Global position of the region's local origin.
+    origin: Pos,
+    /// Global position of the region's visible part.
+    pos: Pos,
+    /// Size of the region's visible part.
+    size: Size,
+}
+
+impl MouseArea {
+    pub(crate) fn new(id: String, origin: Pos, pos: Pos, size: Size) -> Self {
+        Self {
+            id,
+            origin,
+            pos,
+            size,
+        }
+    }
+
+    /// Whether a global position lies within the visible part of the region.
+    pub fn contains(&self, pos: Pos) -> bool {
+        let end = self.pos + self.size;
+        self.pos.x <= pos.x && pos.x < end.x && self.pos.y <= pos.y && pos.y < end.y
+    }
+
+    /// Convert a global position to a position relative to the region.
+    pub fn global_to_local(&self, pos: Pos) -> Pos {
+        pos - self.origin
+    }
+
+    /// The position of a mouse event relative to the region, or [`None`] if
+    /// the event happened outside the region.
+    pub fn event_pos(&self, event: &MouseEvent) -> Option<Pos> {
+        let pos = event_pos(event);
+        self.contains(pos).then(|| self.global_to_local(pos))
+    }
+}
+
+/// A scroll wheel movement over a mouse area, see
+/// [`crate::Terminal::scroll_target`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WheelScroll<'a> {
+    /// The id of the mouse area under the pointer.
+    pub id: &'a str,
+    /// The position of the pointer relative to the mouse area.
+    pub pos: Pos,
+    /// Amount of columns to scroll by. Positive values scroll to the right.
+    pub dx: i32,
+    /// Amount of rows to scroll by. Positive values scroll down.
+    pub dy: i32,
+}
+
+/// The direction of a scroll wheel event, or [`None`] if the event is not a
+/// scroll wheel event.
+pub(crate) fn wheel_direction(event: &MouseEvent) -> Option<(i32, i32)> {
+    match event.kind {
+        MouseEventKind::ScrollUp => Some((0, -1)),
+        MouseEventKind::ScrollDown => Some((0, 1)),
+        MouseEventKind::ScrollLeft => Some((-1, 0)),
+        MouseEventKind::ScrollRight => Some((1, 0)),
+        _ => None,
+    }
+}
+
+/// The global position of a mouse event.
+pub fn event_pos(event: &MouseEvent) -> Pos {
+    Pos::new(event.column.into(), event.row.into())
+}
@@ -0,0 +1,133 @@
+//! Buffering and recording terminal output.
+
+use std::io::{self, Write};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::Size;
+
+/// An asciinema v2 cast file being written.
+struct Recording {
+    out: Box<dyn Write>,
+    start: Instant,
+}
+
+impl Recording {
+    fn event(&mut self, code: &str, data: &str) -> io::Result<()> {
+        let time = self.start.elapsed().as_secs_f64();
+        writeln!(self.out, "[{time:.6}, \"{code}\", {}]", json_string(data))
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// The output of a [`crate::Terminal`].
+///
+/// Everything written is buffered until the output is flushed, at which point
+/// it is written to the inner writer all at once. This way, a frame usually
+/// only requires a single syscall. Optionally, everything written is also
+/// recorded.
+pub(crate) struct Output {
+    inner: Box<dyn Write>,
+    buffer: Vec<u8>,
+    /// Amount of bytes written by the most recent flush.
+    last_flush_len: usize,
+    recording: Option<Recording>,
+}
+
+impl Output {
+    pub(crate) fn new(inner: Box<dyn Write>) -> Self {
+        Self {
+            inner,
+            buffer: vec![],
+            last_flush_len: 0,
+            recording: None,
+        }
+    }
+
+    pub(crate) fn last_flush_len(&self) -> usize {
+        self.last_flush_len
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub(crate) fn start_recording(
+        &mut self,
+        mut out: Box<dyn Write>,
+        size: Size,
+    ) -> io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        writeln!(
+            out,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {timestamp}}}",
+            size.width, size.height
+        )?;
+        self.recording = Some(Recording {
+            out,
+            start: Instant::now(),
+        });
+        Ok(())
+    }
+
+    pub(crate) fn stop_recording(&mut self) -> io::Result<Option<Box<dyn Write>>> {
+        self.flush()?;
+        let Some(mut recording) = self.recording.take() else {
+            return Ok(None);
+        };
+        recording.out.flush()?;
+        Ok(Some(recording.out))
+    }
+
+    pub(crate) fn record_resize(&mut self, size: Size) -> io::Result<()> {
+        if let Some(recording) = &mut self.recording {
+            let data = format!("{}x{}", size.width, size.height);
+            recording.event("r", &data)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.buffer)?;
+        self.inner.flush()?;
+        self.last_flush_len = self.buffer.len();
+
+        // Everything written since the previous flush is recorded as a single
+        // event, usually an entire frame.
+        if let Some(recording) = &mut self.recording {
+            if !self.buffer.is_empty() {
+                let data = String::from_utf8_lossy(&self.buffer).into_owned();
+                recording.event("o", &data)?;
+            }
+        }
+
+        self.buffer.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+//! Querying the terminal.
+//!
+//! Crossterm writes its queries to stdout, which doesn't reach the terminal
+//! while stdout is redirected. These functions write their queries to the
+//! terminal's output instead and read the replies from the controlling
+//! terminal.
+//!
+//! Since the replies are read directly, they may be consumed by crossterm's
+//! event reader instead if it is waiting for input at the same time.
+
+use std::io::{self, Write};
+
+/// Write a query to the output and read the terminal's reply.
+///
+/// Bytes are read until `complete` returns `true` for all bytes read so far.
+/// Returns [`None`] if the terminal does not reply in time.
+#[cfg(unix)]
+fn query<W, F>(out: &mut W, query: &str, complete: F) -> io::Result<Option<Vec<u8>>>
+where
+    W: Write + ?Sized,
+    F: Fn(&[u8]) -> bool,
+{
+    // Without raw mode, the reply would only be readable after a newline
+    let raw_mode = crossterm::terminal::is_raw_mode_enabled()?;
+    if !raw_mode {
+        crossterm::terminal::enable_raw_mode()?;
+    }
+    let result = read_reply(out, query, complete);
+    if !raw_mode {
+        crossterm::terminal::disable_raw_mode()?;
+    }
+    result
+}
+
+#[cfg(unix)]
+fn read_reply<W, F>(out: &mut W, query: &str, complete: F) -> io::Result<Option<Vec<u8>>>
+where
+    W: Write + ?Sized,
+    F: Fn(&[u8]) -> bool,
+{
+    use std::fs::File;
+    use std::io::Read;
+    use std::time::{Duration, Instant};
+
+    use rustix::event::{PollFd, PollFlags};
+
+    let mut tty = File::open("/dev/tty")?;
+    out.write_all(query.as_bytes())?;
+    out.flush()?;
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut reply = vec![];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let mut fds = [PollFd::new(&tty, PollFlags::IN)];
+        match rustix::event::poll(&mut fds, remaining.as_millis() as i32) {
+            Ok(0) => return Ok(None),
+            Ok(_) => {}
+            Err(rustix::io::Errno::INTR) => continue,
+            Err(err) => return Err(err.into()),
+        }
+
+        // Read byte by byte so no input following the reply is consumed
+        let mut byte = [0];
+        if tty.read(&mut byte)? == 0 {
+            return Ok(None);
+        }
+        reply.push(byte[0]);
+        if complete(&reply) {
+            return Ok(Some(reply));
+        }
+    }
+}
+
+/// If the input ends with a reply of the form `prefix params suffix`, return
+/// the params. Params consist of digits separated by semicolons.
+#[cfg(unix)]
+fn reply_params<'a>(input: &'a [u8], prefix: &str, suffix: &str) -> Option<&'a str> {
+    let input = input.strip_suffix(suffix.as_bytes())?;
+    let start = input
+        .iter()
+        .rposition(|b| !b.is_ascii_digit() && *b != b';')
+        .map_or(0, |i| i + 1);
+    if !input[..start].ends_with(prefix.as_bytes()) {
+        return None;
+    }
+    std::str::from_utf8(&input[start..]).ok()
+}
+
+/// Whether the input ends with a reply to a primary device attributes query.
+///
+/// Every terminal answers this query, so it can be sent after queries that
+/// some terminals ignore to avoid waiting for their reply in vain.
+#[cfg(unix)]
+fn ends_with_device_attributes(input: &[u8]) -> bool {
+    reply_params(input, "\x1b[?", "c").is_some()
+}
+
+/// Parse all cursor position reports in the input, returning the column and
+/// row of each.
+#[cfg(unix)]
+fn parse_cursor_positions(input: &[u8]) -> Vec<(u16, u16)> {
+    input
+        .split(|b| *b == b'\x1b')
+        .filter_map(|part| {
+            let (row, col) = reply_params(part, "[", "R")?.split_once(';')?;
+            let row = row.parse::<u16>().ok()?.checked_sub(1)?;
+            let col = col.parse::<u16>().ok()?.checked_sub(1)?;
+            Some((col, row))
+        })
+        .collect()
+}
+
+/// Print the texts, querying the cursor position after each of them.
+///
+/// Returns the column and row of the cursor after each text. All queries are
+/// sent at once, so this only takes a single round trip.
+#[cfg(unix)]
+pub(crate) fn cursor_positions<W: Write + ?Sized>(
+    out: &mut W,
+    texts: &[String],
+) -> io::Result<Vec<(u16, u16)>> {
+    let mut queries = String::new();
+    for text in texts {
+        queries.push_str(text);
+        queries.push_str("\x1b[6n");
+    }
+    let complete =
+        |reply: &[u8]| reply.ends_with(b"R") && parse_cursor_positions(reply).len() == texts.len();
+    query(out, &queries, complete)?
+        .map(|reply| parse_cursor_positions(&reply))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the terminal did not report the cursor position",
+            )
+        })
+}
+
+/// Print the texts, querying the cursor position after each of them.
+///
+/// Returns the column and row of the cursor after each text.
+#[cfg(windows)]
+pub(crate) fn cursor_positions<W: Write + ?Sized>(
+    out: &mut W,
+    texts: &[String],
+) -> io::Result<Vec<(u16, u16)>> {
+    // Uses the console API instead of writing queries to stdout
+    let mut positions = vec![];
+    for text in texts {
+        out.write_all(text.as_bytes())?;
+        out.flush()?;
+        positions.push(crossterm::cursor::position()?);
+    }
+    Ok(positions)
+}
+
+/// Whether the terminal supports the kitty keyboard protocol.
+#[cfg(unix)]
+pub(crate) fn keyboard_enhancement_supported<W: Write + ?Sized>(out: &mut W) -> io::Result<bool> {
+    let Some(reply) = query(out, "\x1b[?u\x1b[c", ends_with_device_attributes)? else {
+        return Ok(false);
+    };
+    Ok(reply
+        .split(|b| *b == b'\x1b')
+        .any(|part| reply_params(part, "[?", "u").is_some()))
+}
+
+/// Whether the terminal supports the kitty keyboard protocol.
+#[cfg(windows)]
+pub(crate) fn keyboard_enhancement_supported<W: Write + ?Sized>(_out: &mut W) -> io::Result<bool> {
+    crossterm::terminal::supports_keyboard_enhancement()
+}
+
+/// Whether the terminal supports mode 2027, or [`None`] if it did not reply.
+#[cfg(unix)]
+pub(crate) fn grapheme_cluster_mode_supported<W: Write + ?Sized>(
+    out: &mut W,
+) -> io::Result<Option<bool>> {
+    // Terminals that don't support DECRQM only answer the second query
+    let Some(reply) = query(out, "\x1b[?2027$p\x1b[c", ends_with_device_attributes)? else {
+        return Ok(None);
+    };
+    let supported = reply
+        .split(|b| *b == b'\x1b')
+        .filter_map(|part| reply_params(part, "[?", "$y"))
+        .any(|params| matches!(params.split_once(';'), Some(("2027", "1" | "2" | "3"))));
+    Ok(Some(supported))
+}
+
+/// Whether the terminal supports mode 2027, or [`None`] if it did not reply.
+#[cfg(windows)]
+pub(crate) fn grapheme_cluster_mode_supported<W: Write + ?Sized>(
+    _out: &mut W,
+) -> io::Result<Option<bool>> {
+    Ok(None) // Replies can't be read
+}
@@ -1,11 +1,15 @@
 use crossterm::style::{ContentStyle, Stylize};
 
-fn merge_cs(base: ContentStyle, cover: ContentStyle) -> ContentStyle {
+fn merge_cs(base: ContentStyle, cover: ContentStyle, merge_attributes: bool) -> ContentStyle {
     ContentStyle {
         foreground_color: cover.foreground_color.or(base.foreground_color),
         background_color: cover.background_color.or(base.background_color),
         underline_color: cover.underline_color.or(base.underline_color),
-        attributes: cover.attributes,
+        attributes: if merge_attributes {
+            base.attributes | cover.attributes
+        } else {
+            cover.attributes
+        },
     }
 }
 
@@ -13,6 +17,9 @@ fn merge_cs(base: ContentStyle, cover: ContentStyle) -> ContentStyle {
 pub struct Style {
     pub content_style: ContentStyle,
     pub opaque: bool,
+    /// Whether the attributes of a transparent style should be added to the
+    /// attributes of the style it covers instead of replacing them.
+    pub merge_attributes: bool,
 }
 
 impl Style {
@@ -30,12 +37,27 @@ impl Style {
         self
     }
 
+    /// Keep the attributes of the covered style, for example to underline text
+    /// without removing its bold attribute.
+    ///
+    /// Has no effect on opaque styles.
+    pub fn merge_attributes(mut self) -> Self {
+        self.merge_attributes = true;
+        self
+    }
+
+    /// Replace the attributes of the covered style. This is the default.
+    pub fn replace_attributes(mut self) -> Self {
+        self.merge_attributes = false;
+        self
+    }
+
     pub fn cover(self, base: ContentStyle) -> ContentStyle {
         if self.opaque {
             return self.content_style;
         }
 
-        merge_cs(base, self.content_style)
+        merge_cs(base, self.content_style, self.merge_attributes)
     }
 }
 
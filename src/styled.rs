@@ -1,16 +1,82 @@
 use std::iter::Peekable;
+use std::ops::Range;
 use std::slice;
+use std::sync::{Arc, Mutex, PoisonError};
 
+use crossterm::style::{Color, Stylize};
 use unicode_segmentation::{GraphemeIndices, Graphemes, UnicodeSegmentation};
 
-use crate::Style;
+use crate::{wrap, Style, WidthDb};
 
+/// Horizontal alignment of text within a wider area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+    /// Distribute the available space between the words.
+    Justify,
+}
+
+/// Where to cut text that is too wide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TruncateAt {
+    /// Keep the end of the text and replace its start by an ellipsis.
+    Start,
+    /// Keep the start and end of the text and replace its middle by an
+    /// ellipsis.
+    Middle,
+    /// Keep the start of the text and replace its end by an ellipsis.
+    End,
+}
+
+/// A `(style, link, until)` tuple as described in [`Styled::styles`].
+type Span = (Style, Option<Arc<str>>, usize);
+
+/// The cached width of a [`Styled`] along with the [`WidthDb`] generation it
+/// was calculated for.
+///
+/// Uses a mutex so a [`Styled`] can be shared between threads.
+#[derive(Debug, Default)]
+struct WidthCache {
+    inner: Mutex<Option<(u64, usize)>>,
+}
+
+impl WidthCache {
+    fn get(&self, generation: u64) -> Option<usize> {
+        let inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner
+            .filter(|(g, _)| *g == generation)
+            .map(|(_, width)| width)
+    }
+
+    fn set(&self, generation: u64, width: usize) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        *inner = Some((generation, width));
+    }
+}
+
+impl Clone for WidthCache {
+    fn clone(&self) -> Self {
+        let inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        Self {
+            inner: Mutex::new(*inner),
+        }
+    }
+}
+
+/// Text with styles applied to parts of it.
+///
+/// The text and styles are shared between clones until one of them is
+/// modified, so cloning is cheap even for long texts.
 #[derive(Debug, Default, Clone)]
 pub struct Styled {
-    text: String,
-    /// List of `(style, until)` tuples. The style should be applied to all
-    /// chars in the range `prev_until..until`.
-    styles: Vec<(Style, usize)>,
+    text: Arc<String>,
+    /// List of `(style, link, until)` tuples. The style and hyperlink target
+    /// should be applied to all chars in the range `prev_until..until`.
+    styles: Arc<Vec<Span>>,
+    /// Width of the text, copied to clones along with the text.
+    width: WidthCache,
 }
 
 impl Styled {
@@ -22,58 +88,163 @@ impl Styled {
         Self::default().then_plain(text)
     }
 
-    pub fn then<S: AsRef<str>>(mut self, text: S, style: Style) -> Self {
-        let text = text.as_ref();
-        if !text.is_empty() {
-            self.text.push_str(text);
-            self.styles.push((style, self.text.len()));
+    /// Create an empty text with space for at least `capacity` bytes of text.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            text: Arc::new(String::with_capacity(capacity)),
+            styles: Arc::default(),
+            width: WidthCache::default(),
         }
-        self
+    }
+
+    pub fn then<S: AsRef<str>>(self, text: S, style: Style) -> Self {
+        self.then_span(text.as_ref(), style, None)
     }
 
     pub fn then_plain<S: AsRef<str>>(self, text: S) -> Self {
         self.then(text, Style::new())
     }
 
-    pub fn and_then(mut self, mut other: Self) -> Self {
+    /// Like [`Self::then`], but the text is also part of a hyperlink to `url`.
+    ///
+    /// Whether the hyperlink is clickable depends on the terminal. For more
+    /// details, see [`crate::Terminal::hyperlinks`].
+    pub fn then_link<S: AsRef<str>>(self, text: S, url: &str, style: Style) -> Self {
+        self.then_span(text.as_ref(), style, Some(url.into()))
+    }
+
+    fn then_span(mut self, text: &str, style: Style, link: Option<Arc<str>>) -> Self {
+        self.push_span(text, style, link);
+        self
+    }
+
+    fn push_span(&mut self, text: &str, style: Style, link: Option<Arc<str>>) {
+        if !text.is_empty() {
+            Arc::make_mut(&mut self.text).push_str(text);
+            Arc::make_mut(&mut self.styles).push((style, link, self.text.len()));
+            self.width = WidthCache::default();
+        }
+    }
+
+    /// Like [`Self::then`], but without taking ownership.
+    pub fn push_str<S: AsRef<str>>(&mut self, text: S, style: Style) {
+        self.push_span(text.as_ref(), style, None);
+    }
+
+    /// Like [`Self::then_plain`], but without taking ownership.
+    pub fn push_plain<S: AsRef<str>>(&mut self, text: S) {
+        self.push_str(text, Style::new());
+    }
+
+    /// Like [`Self::and_then`], but without taking ownership.
+    pub fn push_styled(&mut self, other: Self) {
+        if self.text.is_empty() {
+            *self = other;
+            return;
+        }
+
         let delta = self.text.len();
-        for (_, until) in &mut other.styles {
-            *until += delta;
+        let styles = Arc::make_mut(&mut self.styles);
+        for (style, link, until) in other.styles.iter() {
+            styles.push((*style, link.clone(), until + delta));
+        }
+        Arc::make_mut(&mut self.text).push_str(&other.text);
+        self.width = WidthCache::default();
+    }
+
+    /// Make the entire text part of a hyperlink to `url`, replacing any
+    /// existing hyperlinks.
+    pub fn with_link(mut self, url: &str) -> Self {
+        let url: Arc<str> = url.into();
+        for (_, link, _) in Arc::make_mut(&mut self.styles) {
+            *link = Some(url.clone());
         }
+        self
+    }
 
-        self.text.push_str(&other.text);
-        self.styles.extend(other.styles);
+    pub fn and_then(mut self, other: Self) -> Self {
+        self.push_styled(other);
         self
     }
 
+    /// Concatenate multiple styled texts, inserting a separator between each
+    /// two of them.
+    pub fn join<I, S>(parts: I, separator: S) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Self>,
+        S: Into<Self>,
+    {
+        let separator = separator.into();
+        let mut result = Self::default();
+        for (i, part) in parts.into_iter().enumerate() {
+            if i > 0 {
+                result = result.and_then(separator.clone());
+            }
+            result = result.and_then(part.into());
+        }
+        result
+    }
+
     pub fn text(&self) -> &str {
         &self.text
     }
 
+    /// The width of the text in terminal columns.
+    ///
+    /// Equivalent to `widthdb.width(self.text())`, but the result is cached
+    /// until the text is modified or the widths in the [`WidthDb`] change.
+    pub fn width(&self, widthdb: &mut WidthDb) -> usize {
+        let generation = widthdb.generation();
+        if let Some(width) = self.width.get(generation) {
+            return width;
+        }
+
+        let width = widthdb.width(&self.text);
+        self.width.set(generation, width);
+        width
+    }
+
+    /// Like [`Self::width`], but ignoring trailing whitespace, for example the
+    /// newline at the end of a line.
+    ///
+    /// Only the width of text without trailing whitespace is cached.
+    pub fn width_trim_end(&self, widthdb: &mut WidthDb) -> usize {
+        let trimmed = self.text.trim_end();
+        if trimmed.len() == self.text.len() {
+            self.width(widthdb)
+        } else {
+            widthdb.width(trimmed)
+        }
+    }
+
     pub fn split_at(self, mid: usize) -> (Self, Self) {
         let (left_text, right_text) = self.text.split_at(mid);
 
         let mut left_styles = vec![];
         let mut right_styles = vec![];
         let mut from = 0;
-        for (style, until) in self.styles {
+        for (style, link, until) in self.styles.iter() {
+            let (style, until) = (*style, *until);
             if from < mid {
-                left_styles.push((style, until.min(mid)));
+                left_styles.push((style, link.clone(), until.min(mid)));
             }
             if mid < until {
-                right_styles.push((style, until.saturating_sub(mid)));
+                right_styles.push((style, link.clone(), until.saturating_sub(mid)));
             }
             from = until;
         }
 
         let left = Self {
-            text: left_text.to_string(),
-            styles: left_styles,
+            text: Arc::new(left_text.to_string()),
+            styles: Arc::new(left_styles),
+            width: WidthCache::default(),
         };
 
         let right = Self {
-            text: right_text.to_string(),
-            styles: right_styles,
+            text: Arc::new(right_text.to_string()),
+            styles: Arc::new(right_styles),
+            width: WidthCache::default(),
         };
 
         (left, right)
@@ -97,12 +268,314 @@ impl Styled {
         lines
     }
 
+    /// Apply a style on top of the existing styles of a byte range of the
+    /// text.
+    ///
+    /// The style covers the existing styles like [`Style::cover`]. Panics if
+    /// the range does not lie on char boundaries.
+    pub fn style_range(self, range: Range<usize>, style: Style) -> Self {
+        let (left, rest) = self.split_at(range.start);
+        let (mut middle, right) = rest.split_at(range.end - range.start);
+
+        for (base, _, _) in Arc::make_mut(&mut middle.styles) {
+            base.content_style = style.cover(base.content_style);
+            base.opaque |= style.opaque;
+        }
+
+        left.and_then(middle).and_then(right)
+    }
+
+    /// Like [`Self::style_range`], but the range is measured in graphemes
+    /// instead of bytes.
+    pub fn style_grapheme_range(self, range: Range<usize>, style: Style) -> Self {
+        let mut boundaries = self
+            .grapheme_indices()
+            .map(|(i, _)| i)
+            .chain([self.text.len()]);
+        let start = boundaries.nth(range.start).unwrap_or(self.text.len());
+        let end = match range.end.checked_sub(range.start + 1) {
+            Some(n) => boundaries.nth(n).unwrap_or(self.text.len()),
+            None => start,
+        };
+        self.style_range(start..end, style)
+    }
+
+    /// Apply a style on top of all non-overlapping occurrences of a pattern
+    /// like [`Self::style_range`].
+    ///
+    /// Does nothing if the pattern is empty.
+    pub fn highlight_matches(self, pattern: &str, style: Style) -> Self {
+        if pattern.is_empty() {
+            return self;
+        }
+
+        let ranges = self
+            .text
+            .match_indices(pattern)
+            .map(|(i, m)| i..i + m.len())
+            .collect::<Vec<_>>();
+        self.style_ranges(&ranges, style)
+    }
+
+    /// Apply a style on top of all non-overlapping matches of a regex like
+    /// [`Self::style_range`].
+    #[cfg(feature = "regex")]
+    pub fn highlight_regex(self, regex: &regex::Regex, style: Style) -> Self {
+        let ranges = regex
+            .find_iter(&self.text)
+            .map(|m| m.range())
+            .collect::<Vec<_>>();
+        self.style_ranges(&ranges, style)
+    }
+
+    /// Apply a style to multiple sorted, non-overlapping byte ranges like
+    /// [`Self::style_range`], in a single pass over the existing styles.
+    fn style_ranges(self, ranges: &[Range<usize>], style: Style) -> Self {
+        let mut ranges = ranges.iter().filter(|r| !r.is_empty()).peekable();
+        if ranges.peek().is_none() {
+            return self;
+        }
+
+        let mut styles = Vec::with_capacity(self.styles.len());
+        let mut from = 0;
+        for (base, link, until) in self.styles.iter() {
+            while from < *until {
+                while ranges.next_if(|r| r.end <= from).is_some() {}
+                let (to, covered) = match ranges.peek() {
+                    Some(r) if r.start <= from => (r.end.min(*until), true),
+                    Some(r) => (r.start.min(*until), false),
+                    None => (*until, false),
+                };
+
+                let mut base = *base;
+                if covered {
+                    base.content_style = style.cover(base.content_style);
+                    base.opaque |= style.opaque;
+                }
+                styles.push((base, link.clone(), to));
+                from = to;
+            }
+        }
+
+        Self {
+            text: self.text,
+            styles: Arc::new(styles),
+            width: self.width,
+        }
+    }
+
+    /// Cut the text to at most the given width, replacing the removed part by
+    /// an ellipsis.
+    ///
+    /// The text is only cut at grapheme boundaries, so the result may be
+    /// narrower than the given width if wide graphemes are involved. The
+    /// ellipsis uses the style of the first removed grapheme. Text that
+    /// already fits is returned unchanged.
+    pub fn truncate_to_width(self, widthdb: &mut WidthDb, max_cols: usize, at: TruncateAt) -> Self {
+        // Index, width and style of each grapheme
+        let mut graphemes = vec![];
+        let mut total = 0;
+        for (i, style, g) in self.styled_grapheme_indices() {
+            let width = widthdb.grapheme_width(g, total) as usize;
+            total += width;
+            graphemes.push((i, width, style));
+        }
+        if total <= max_cols {
+            return self;
+        }
+        let Some(available) = max_cols.checked_sub(1) else {
+            return Self::default();
+        };
+
+        let (left_cols, right_cols) = match at {
+            TruncateAt::Start => (0, available),
+            TruncateAt::Middle => (available.div_ceil(2), available / 2),
+            TruncateAt::End => (available, 0),
+        };
+
+        let mut width = 0;
+        let left_end = graphemes
+            .iter()
+            .take_while(|(_, w, _)| {
+                width += w;
+                width <= left_cols
+            })
+            .count();
+
+        let mut width = 0;
+        let right_start = graphemes.len()
+            - graphemes
+                .iter()
+                .rev()
+                .take_while(|(_, w, _)| {
+                    width += w;
+                    width <= right_cols
+                })
+                .count();
+
+        // Since the text is too wide, at least one grapheme is removed
+        let (mid, _, style) = graphemes[left_end];
+        let right = graphemes
+            .get(right_start)
+            .map(|g| g.0)
+            .unwrap_or(self.text.len());
+
+        let (left, rest) = self.split_at(mid);
+        let (_, right) = rest.split_at(right - mid);
+        left.then("…", style).and_then(right)
+    }
+
+    /// Pad the text with spaces to the given width.
+    ///
+    /// The alignment determines where the spaces are inserted. When centering,
+    /// an odd amount of spaces is biased towards the right. When justifying,
+    /// trailing whitespace is removed and the spaces are distributed between
+    /// the words, falling back to left alignment if there is only one word.
+    /// Text that is already at least as wide is returned unchanged.
+    pub fn pad_to_width(
+        self,
+        widthdb: &mut WidthDb,
+        width: usize,
+        alignment: Alignment,
+        fill: Style,
+    ) -> Self {
+        let padding = width.saturating_sub(self.width(widthdb));
+        let (left, right) = match alignment {
+            Alignment::Left => (0, padding),
+            Alignment::Center => (padding / 2, padding - padding / 2),
+            Alignment::Right => (padding, 0),
+            Alignment::Justify => return self.justify(widthdb, width, fill),
+        };
+
+        Self::new(" ".repeat(left), fill)
+            .and_then(self)
+            .then(" ".repeat(right), fill)
+    }
+
+    fn justify(mut self, widthdb: &mut WidthDb, width: usize, fill: Style) -> Self {
+        self.trim_end();
+        let padding = width.saturating_sub(self.width(widthdb));
+
+        // End index and style of each whitespace run between two words
+        let mut gaps = vec![];
+        let mut gap = None;
+        let mut seen_word = false;
+        for (i, style, g) in self.styled_grapheme_indices() {
+            if g.chars().all(|c| c.is_whitespace()) {
+                gap = Some(style).filter(|_| seen_word);
+            } else {
+                if let Some(style) = gap.take() {
+                    gaps.push((i, style));
+                }
+                seen_word = true;
+            }
+        }
+
+        if padding == 0 || gaps.is_empty() {
+            return self.pad_to_width(widthdb, width, Alignment::Left, fill);
+        }
+
+        let mut result = Self::default();
+        let mut rest = self;
+        let mut offset = 0;
+        for (n, (i, style)) in gaps.iter().enumerate() {
+            let spaces = padding / gaps.len() + usize::from(n < padding % gaps.len());
+            let (left, right) = rest.split_at(i - offset);
+            result = result.and_then(left).then(" ".repeat(spaces), *style);
+            rest = right;
+            offset = *i;
+        }
+        result.and_then(rest)
+    }
+
+    /// Wrap the text to the given width and align the resulting rows.
+    ///
+    /// Trailing whitespace, including newlines, is removed from the rows. When
+    /// justifying, the last row of each line is aligned left.
+    pub fn wrap_aligned(
+        self,
+        widthdb: &mut WidthDb,
+        width: usize,
+        alignment: Alignment,
+    ) -> Vec<Self> {
+        let indices = widthdb.wrap(self.text(), width);
+        let rows = self.split_at_indices(&indices);
+        wrap::align_rows(widthdb, rows, width, alignment)
+    }
+
+    /// The part of the text visible between two columns, for example in a
+    /// horizontally scrolled viewport.
+    ///
+    /// Wide graphemes only partially inside the range and tabs are replaced by
+    /// spaces of the same style. The text should not contain any newlines.
+    pub fn slice_columns(&self, widthdb: &mut WidthDb, columns: Range<usize>) -> Self {
+        let mut result = Self::default();
+        let mut col = 0;
+        for (_, style, link, g) in self.linked_grapheme_indices() {
+            let width = widthdb.grapheme_width(g, col) as usize;
+            let start = col.max(columns.start);
+            let end = (col + width).min(columns.end);
+            let keep = match width {
+                0 => columns.contains(&col),
+                _ => start == col && end == col + width && g != "\t",
+            };
+            if keep {
+                result = result.then_span(g, style, link.cloned());
+            } else if start < end {
+                result = result.then_span(&" ".repeat(end - start), style, link.cloned());
+            }
+
+            col += width;
+            if col >= columns.end {
+                break;
+            }
+        }
+        result
+    }
+
+    /// The part of the text in a byte range, along with its styles.
+    pub(crate) fn substring(&self, range: Range<usize>) -> Self {
+        let mut styles = vec![];
+        let mut from = 0;
+        for (style, link, until) in self.styles.iter() {
+            if from < range.end && range.start < *until {
+                let until = (*until).min(range.end) - range.start;
+                styles.push((*style, link.clone(), until));
+            }
+            from = *until;
+        }
+
+        Self {
+            text: Arc::new(self.text[range].to_string()),
+            styles: Arc::new(styles),
+            width: WidthCache::default(),
+        }
+    }
+
+    /// Split the text into lines at newlines, like [`str::lines`].
+    ///
+    /// The lines don't contain their trailing newline. Unlike
+    /// [`Self::split_at_indices`], this doesn't consume the text.
+    pub fn lines(&self) -> impl Iterator<Item = Self> + '_ {
+        let mut start = 0;
+        self.text.split_inclusive('\n').map(move |line| {
+            let range = start..start + line.trim_end_matches('\n').len();
+            start += line.len();
+            self.substring(range)
+        })
+    }
+
     pub fn trim_end(&mut self) {
-        self.text = self.text.trim_end().to_string();
+        let text_len = self.text.trim_end().len();
+        if text_len == self.text.len() {
+            return;
+        }
+        Arc::make_mut(&mut self.text).truncate(text_len);
+        self.width = WidthCache::default();
 
-        let text_len = self.text.len();
+        let styles = Arc::make_mut(&mut self.styles);
         let mut styles_len = 0;
-        for (_, until) in &mut self.styles {
+        for (_, _, until) in styles.iter_mut() {
             styles_len += 1;
             if *until >= text_len {
                 *until = text_len;
@@ -110,31 +583,244 @@ impl Styled {
             }
         }
 
-        while self.styles.len() > styles_len {
-            self.styles.pop();
+        styles.truncate(styles_len);
+    }
+}
+
+////////////////////////////
+// Transforming graphemes //
+////////////////////////////
+
+impl Styled {
+    /// Replace each grapheme by the result of a function, keeping the style
+    /// of the grapheme.
+    pub fn map_graphemes<F, S>(&self, mut f: F) -> Self
+    where
+        F: FnMut(&str) -> S,
+        S: AsRef<str>,
+    {
+        self.linked_grapheme_indices()
+            .fold(Self::default(), |acc, (_, style, link, g)| {
+                acc.then_span(f(g).as_ref(), style, link.cloned())
+            })
+    }
+
+    pub fn to_uppercase(&self) -> Self {
+        self.map_graphemes(str::to_uppercase)
+    }
+
+    pub fn to_lowercase(&self) -> Self {
+        self.map_graphemes(str::to_lowercase)
+    }
+
+    /// Replace each grapheme except for newlines by a mask character.
+    pub fn redact(&self, mask: char) -> Self {
+        self.map_graphemes(|g| {
+            if g.ends_with('\n') {
+                "\n".to_string()
+            } else {
+                mask.to_string()
+            }
+        })
+    }
+
+    /// Replace control characters except for newlines and tabs by their
+    /// visible Unicode control pictures, for example `␛` for escape.
+    pub fn replace_control_chars(&self) -> Self {
+        self.map_graphemes(|g| {
+            g.chars()
+                .map(|c| match c {
+                    '\n' | '\t' => c,
+                    '\0'..='\x1f' => char::from_u32(0x2400 + c as u32).unwrap_or(c),
+                    '\x7f' => '␡',
+                    c => c,
+                })
+                .collect::<String>()
+        })
+    }
+}
+
+///////////////
+// Gradients //
+///////////////
+
+impl Styled {
+    /// Color the text with a gradient from one RGB color to another.
+    ///
+    /// Each grapheme is colored according to the position of its center
+    /// column, so wide graphemes don't skew the gradient.
+    pub fn gradient(&self, widthdb: &mut WidthDb, from: (u8, u8, u8), to: (u8, u8, u8)) -> Self {
+        self.map_colors(widthdb, from, to, |style, color| {
+            style.content_style.foreground_color = Some(color);
+        })
+    }
+
+    /// Like [`Self::gradient`], but for the background color.
+    pub fn gradient_bg(&self, widthdb: &mut WidthDb, from: (u8, u8, u8), to: (u8, u8, u8)) -> Self {
+        self.map_colors(widthdb, from, to, |style, color| {
+            style.content_style.background_color = Some(color);
+        })
+    }
+
+    fn map_colors<F>(
+        &self,
+        widthdb: &mut WidthDb,
+        from: (u8, u8, u8),
+        to: (u8, u8, u8),
+        f: F,
+    ) -> Self
+    where
+        F: Fn(&mut Style, Color),
+    {
+        let total = self.width(widthdb);
+        let lerp = |a: u8, b: u8, t: f32| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+        let mut result = Self::default();
+        let mut col = 0;
+        for (_, mut style, link, g) in self.linked_grapheme_indices() {
+            let width = widthdb.grapheme_width(g, col) as usize;
+            let center = col as f32 + width.saturating_sub(1) as f32 / 2.0;
+            let t = center / total.saturating_sub(1).max(1) as f32;
+            let t = t.clamp(0.0, 1.0);
+            col += width;
+
+            let color = Color::Rgb {
+                r: lerp(from.0, to.0, t),
+                g: lerp(from.1, to.1, t),
+                b: lerp(from.2, to.2, t),
+            };
+            f(&mut style, color);
+            result = result.then_span(g, style, link.cloned());
         }
+        result
+    }
+}
+
+/////////////////////
+// Inline markdown //
+/////////////////////
+
+impl Styled {
+    /// Parse text containing inline markdown.
+    ///
+    /// Supports bold (`**text**` or `__text__`), italics (`*text*` or
+    /// `_text_`), code spans (`` `code` ``) and links (`[text](url)`), as well
+    /// as backslash escapes. Links are displayed as their underlined text and
+    /// turned into hyperlinks.
+    /// Block-level markup like headings or lists is not supported. Delimiters
+    /// without a matching closing delimiter are kept as they are.
+    pub fn from_markdown_inline(text: &str) -> Self {
+        parse_markdown_inline(text, Style::new())
+    }
+}
+
+fn parse_markdown_inline(text: &str, style: Style) -> Styled {
+    let mut result = Styled::default();
+    let mut plain = String::new();
+    let mut prev = None;
+
+    let mut i = 0;
+    while let Some(ch) = text[i..].chars().next() {
+        let rest = &text[i..];
+        let span = markdown_span(rest, prev, style);
+        if let Some((len, styled)) = span {
+            result = result.then(&plain, style).and_then(styled);
+            plain.clear();
+            prev = rest[..len].chars().next_back();
+            i += len;
+            continue;
+        }
+
+        match rest[ch.len_utf8()..].chars().next() {
+            Some(next) if ch == '\\' && next.is_ascii_punctuation() => {
+                plain.push(next);
+                i += ch.len_utf8() + next.len_utf8();
+            }
+            _ => {
+                plain.push(ch);
+                i += ch.len_utf8();
+            }
+        }
+        prev = Some(ch);
+    }
+
+    result.then(plain, style)
+}
+
+/// Try to parse a span at the start of the text.
+///
+/// Returns the length of the span in bytes along with its styled contents.
+fn markdown_span(text: &str, prev: Option<char>, style: Style) -> Option<(usize, Styled)> {
+    if let Some(rest) = text.strip_prefix('`') {
+        let end = rest.find('`')?;
+        return Some((end + 2, Styled::new(&rest[..end], style.reverse())));
+    }
+
+    if let Some(rest) = text.strip_prefix('[') {
+        let mid = rest.find("](")?;
+        let end = mid + 2 + rest[mid + 2..].find(')')?;
+        let url = &rest[mid + 2..end];
+        let inner = parse_markdown_inline(&rest[..mid], style.underlined()).with_link(url);
+        return Some((end + 2, inner));
+    }
+
+    // Underscores inside words don't count, so that snake_case works
+    let intraword = prev.is_some_and(|c| c.is_alphanumeric());
+    for (delim, bold) in [("**", true), ("__", true), ("*", false), ("_", false)] {
+        if delim.starts_with('_') && intraword {
+            continue;
+        }
+        let Some(rest) = text.strip_prefix(delim) else {
+            continue;
+        };
+        // Like in CommonMark, emphasis may not start or end with whitespace
+        let Some(end) = rest.find(delim).filter(|end| {
+            let inner = &rest[..*end];
+            !inner.is_empty() && inner.trim() == inner
+        }) else {
+            continue;
+        };
+        let style = if bold { style.bold() } else { style.italic() };
+        let inner = parse_markdown_inline(&rest[..end], style);
+        return Some((end + 2 * delim.len(), inner));
     }
+
+    None
 }
 
 //////////////////////////////
 // Iterating over graphemes //
 //////////////////////////////
 
-pub struct StyledGraphemeIndices<'a> {
+/// Like [`StyledGraphemeIndices`], but also yields the hyperlink target of
+/// each grapheme.
+pub(crate) struct LinkedGraphemeIndices<'a> {
     text: GraphemeIndices<'a>,
-    styles: Peekable<slice::Iter<'a, (Style, usize)>>,
+    styles: Peekable<slice::Iter<'a, Span>>,
 }
 
-impl<'a> Iterator for StyledGraphemeIndices<'a> {
-    type Item = (usize, Style, &'a str);
+impl<'a> Iterator for LinkedGraphemeIndices<'a> {
+    type Item = (usize, Style, Option<&'a Arc<str>>, &'a str);
 
     fn next(&mut self) -> Option<Self::Item> {
         let (gi, grapheme) = self.text.next()?;
-        let (mut style, mut until) = **self.styles.peek().expect("styles cover entire text");
-        while gi >= until {
+        loop {
+            let (style, link, until) = self.styles.peek().expect("styles cover entire text");
+            if gi < *until {
+                return Some((gi, *style, link.as_ref(), grapheme));
+            }
             self.styles.next();
-            (style, until) = **self.styles.peek().expect("styles cover entire text");
         }
+    }
+}
+
+pub struct StyledGraphemeIndices<'a>(LinkedGraphemeIndices<'a>);
+
+impl<'a> Iterator for StyledGraphemeIndices<'a> {
+    type Item = (usize, Style, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (gi, style, _, grapheme) = self.0.next()?;
         Some((gi, style, grapheme))
     }
 }
@@ -149,7 +835,11 @@ impl Styled {
     }
 
     pub fn styled_grapheme_indices(&self) -> StyledGraphemeIndices<'_> {
-        StyledGraphemeIndices {
+        StyledGraphemeIndices(self.linked_grapheme_indices())
+    }
+
+    pub(crate) fn linked_grapheme_indices(&self) -> LinkedGraphemeIndices<'_> {
+        LinkedGraphemeIndices {
             text: self.grapheme_indices(),
             styles: self.styles.iter().peekable(),
         }
@@ -160,6 +850,28 @@ impl Styled {
 // Converting to Styled //
 //////////////////////////
 
+/// Concatenate multiple values that can be converted into a [`Styled`].
+///
+/// For example, `styled!("Hello ", ("world", style), "!")` is equivalent to
+/// `Styled::new_plain("Hello ").then("world", style).then_plain("!")`.
+#[macro_export]
+macro_rules! styled {
+    () => {
+        $crate::Styled::default()
+    };
+    ( $( $part:expr ),+ $(,)? ) => {
+        $crate::Styled::default() $( .and_then($crate::Styled::from($part)) )+
+    };
+}
+
+impl<S: Into<Self>> Extend<S> for Styled {
+    fn extend<T: IntoIterator<Item = S>>(&mut self, iter: T) {
+        for part in iter {
+            self.push_styled(part.into());
+        }
+    }
+}
+
 impl From<&str> for Styled {
     fn from(text: &str) -> Self {
         Self::new_plain(text)
@@ -168,7 +880,16 @@ impl From<&str> for Styled {
 
 impl From<String> for Styled {
     fn from(text: String) -> Self {
-        Self::new_plain(text)
+        // Avoid copying the text
+        if text.is_empty() {
+            return Self::default();
+        }
+        let styles = vec![(Style::new(), None, text.len())];
+        Self {
+            text: Arc::new(text),
+            styles: Arc::new(styles),
+            width: WidthCache::default(),
+        }
     }
 }
 
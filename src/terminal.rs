@@ -1,14 +1,21 @@
 //! Displaying frames on a terminal.
 
-use std::io::{self, Write};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
 use std::mem;
+use std::ops::ControlFlow;
+use std::process::{Command, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::cursor::{Hide, MoveTo, SetCursorStyle, Show};
 use crossterm::event::{
-    DisableBracketedPaste, EnableBracketedPaste, KeyboardEnhancementFlags,
-    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+    EnableFocusChange, EnableMouseCapture, Event, KeyCode, KeyModifiers, KeyboardEnhancementFlags,
+    MouseEvent, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
 };
-use crossterm::style::{PrintStyledContent, StyledContent};
+use crossterm::style::{Attribute, Color, ContentStyle, Print, SetAttribute, SetStyle};
 use crossterm::terminal::{
     BeginSynchronizedUpdate, Clear, ClearType, EndSynchronizedUpdate, EnterAlternateScreen,
     LeaveAlternateScreen, SetTitle,
@@ -16,7 +23,145 @@ use crossterm::terminal::{
 use crossterm::{ExecutableCommand, QueueableCommand};
 
 use crate::buffer::Buffer;
-use crate::{AsyncWidget, Frame, Size, Widget, WidthDb};
+use crate::commands::{
+    Notify, PopTitle, PushTitle, QueryColor, RingBell, SetClipboard, SetCursorColor,
+    SetGraphemeClusterMode, SetHyperlink,
+};
+use crate::output::Output;
+use crate::{color, mouse, query};
+use crate::{
+    AsyncWidget, ColorDepth, CursorStyle, Error, Frame, MouseArea, Osc52Clipboard, Pos, Size,
+    WheelScroll, Widget, WidthDb, WidthEstimationMethod,
+};
+
+/// Escape sequence used to show desktop notifications.
+///
+/// Which protocol is supported depends on the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationProtocol {
+    /// `OSC 9`, supported by iTerm2, kitty and others. Only has a single text
+    /// field, so title and body are combined.
+    Osc9,
+    /// `OSC 777`, supported by urxvt, foot, WezTerm, Ghostty and others.
+    Osc777,
+}
+
+/// Which terminal features [`Terminal`] should enable while it is active.
+///
+/// The features are enabled when the terminal is created or unsuspended, and
+/// disabled again when it is suspended or dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalOptions {
+    /// Draw to the alternate screen, leaving the normal screen contents intact.
+    pub alternate_screen: bool,
+    /// Put the terminal into raw mode, disabling line buffering and echoing.
+    pub raw_mode: bool,
+    /// Report pasted text as [`crossterm::event::Event::Paste`] instead of
+    /// individual key presses. Ignored on Windows.
+    pub bracketed_paste: bool,
+    /// Keyboard enhancement flags (also known as the kitty keyboard protocol)
+    /// to push. Nothing is pushed if the flags are empty or the terminal
+    /// doesn't support keyboard enhancement. See
+    /// [`Terminal::keyboard_enhancement`].
+    pub keyboard_enhancement: KeyboardEnhancementFlags,
+    /// Report mouse events. See [`Terminal::mouse_capture`].
+    pub mouse_capture: bool,
+    /// Report when the terminal window gains or loses focus. See
+    /// [`Terminal::focused`].
+    pub focus_change: bool,
+    /// Query the terminal's default colors when the terminal is created. Only
+    /// done in raw mode. See [`Terminal::query_default_colors`].
+    pub query_default_colors: bool,
+    /// Save the window title and restore it when the terminal is suspended or
+    /// dropped. Not all terminals support this.
+    pub restore_title: bool,
+}
+
+impl Default for TerminalOptions {
+    fn default() -> Self {
+        Self {
+            alternate_screen: true,
+            raw_mode: true,
+            bracketed_paste: true,
+            keyboard_enhancement: KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES,
+            mouse_capture: false,
+            focus_change: false,
+            query_default_colors: false,
+            restore_title: false,
+        }
+    }
+}
+
+impl TerminalOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_alternate_screen(mut self, active: bool) -> Self {
+        self.alternate_screen = active;
+        self
+    }
+
+    pub fn with_raw_mode(mut self, active: bool) -> Self {
+        self.raw_mode = active;
+        self
+    }
+
+    pub fn with_bracketed_paste(mut self, active: bool) -> Self {
+        self.bracketed_paste = active;
+        self
+    }
+
+    pub fn with_keyboard_enhancement(mut self, flags: KeyboardEnhancementFlags) -> Self {
+        self.keyboard_enhancement = flags;
+        self
+    }
+
+    pub fn with_mouse_capture(mut self, active: bool) -> Self {
+        self.mouse_capture = active;
+        self
+    }
+
+    pub fn with_focus_change(mut self, active: bool) -> Self {
+        self.focus_change = active;
+        self
+    }
+
+    pub fn with_query_default_colors(mut self, active: bool) -> Self {
+        self.query_default_colors = active;
+        self
+    }
+
+    pub fn with_restore_title(mut self, active: bool) -> Self {
+        self.restore_title = active;
+        self
+    }
+}
+
+/// The future returned by the drawing function passed to
+/// [`Terminal::run_async`].
+#[cfg(feature = "events")]
+pub type DrawFuture<'a, E> =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), E>> + Send + 'a>>;
+
+/// Statistics about the most recently presented frame, see
+/// [`Terminal::metrics`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameMetrics {
+    /// Time spent drawing the widget to the frame, including any sizing done by
+    /// the widgets while drawing. Only measured when the frame was drawn by
+    /// one of the functions presenting widgets or by [`Terminal::run`].
+    pub draw: Duration,
+    /// Time spent comparing the frame to the previous frame and generating the
+    /// escape sequences to update the screen.
+    pub diff: Duration,
+    /// Time spent writing to the terminal.
+    pub write: Duration,
+    /// Amount of cells that differed from the previous frame.
+    pub cells_changed: usize,
+    /// Amount of bytes written to the terminal.
+    pub bytes_written: usize,
+}
 
 /// Wrapper that manages terminal output.
 ///
@@ -25,7 +170,7 @@ use crate::{AsyncWidget, Frame, Size, Widget, WidthDb};
 /// terminal in a weird state even if your program crashes.
 pub struct Terminal {
     /// Render target.
-    out: Box<dyn Write>,
+    out: Output,
     /// The frame being currently rendered.
     frame: Frame,
     /// Buffer from the previous frame.
@@ -33,6 +178,119 @@ pub struct Terminal {
     /// When the screen is updated next, it must be cleared and redrawn fully
     /// instead of performing an incremental update.
     full_redraw: bool,
+    /// Whether hyperlinks should be emitted as OSC 8 escape sequences.
+    hyperlinks: bool,
+    /// Colors are converted to this depth before being displayed.
+    color_depth: ColorDepth,
+    /// Terminal features enabled while the terminal is not suspended.
+    options: TerminalOptions,
+    /// Mouse areas registered while drawing the previous frame.
+    mouse_areas: Vec<MouseArea>,
+    /// Whether the cursor is drawn as part of the frame.
+    soft_cursor: bool,
+    /// Amount of lines to scroll per scroll wheel event.
+    wheel_scroll: Option<u16>,
+    /// Maximum amount of frames presented per second.
+    max_fps: Option<u32>,
+    /// When the previous frame was presented.
+    last_present: Option<Instant>,
+    /// Amount of bytes written to display the previous frame.
+    last_present_bytes: usize,
+    /// Time spent drawing the current frame, if known.
+    draw_duration: Duration,
+    /// Statistics about the previous frame, if enabled.
+    metrics: Option<FrameMetrics>,
+    /// Cursor style of the previous frame.
+    prev_cursor_style: CursorStyle,
+    /// Cursor color of the previous frame, as sent to the terminal.
+    prev_cursor_color: Option<(u8, u8, u8)>,
+    /// The window title most recently set by a frame.
+    prev_title: Option<String>,
+    /// How desktop notifications should be shown, if at all.
+    notification_protocol: Option<NotificationProtocol>,
+    /// The terminal's default foreground color, if known.
+    default_fg: Option<(u8, u8, u8)>,
+    /// The terminal's default background color, if known.
+    default_bg: Option<(u8, u8, u8)>,
+    /// Whether the terminal supports keyboard enhancement, once known.
+    keyboard_enhancement_supported: Option<bool>,
+    /// Keyboard enhancement flags currently pushed.
+    keyboard_enhancement: KeyboardEnhancementFlags,
+    /// Whether the terminal is currently suspended.
+    suspended: bool,
+    /// Whether the output is known to be the terminal, so queries written to
+    /// it are answered.
+    queries: bool,
+}
+
+/// Reconstruct the input crossterm parsed into a key event.
+///
+/// Crossterm doesn't understand replies to OSC queries and reports them as a
+/// series of key presses instead.
+fn key_input(event: &Event) -> Option<String> {
+    let Event::Key(key) = event else {
+        return None;
+    };
+    let c = match key.code {
+        KeyCode::Char(c) => c,
+        KeyCode::Esc => return Some("\x1b".to_string()),
+        _ => return None,
+    };
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        // BEL is the only control character that may be part of a reply
+        return (c == 'g').then(|| "\x07".to_string());
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        return Some(format!("\x1b{c}"));
+    }
+    Some(c.to_string())
+}
+
+/// Check whether an output is a terminal.
+///
+/// Dumb terminals are accepted as well. They display plain text since
+/// [`ColorDepth::detect`] returns [`ColorDepth::NoColor`] for them.
+fn check_terminal(is_terminal: bool) -> Result<(), Error> {
+    if is_terminal {
+        Ok(())
+    } else {
+        Err(Error::NotATty)
+    }
+}
+
+/// Guess whether the terminal supports OSC 8 hyperlinks based on environment
+/// variables.
+///
+/// `FORCE_HYPERLINK` can be set to `1` or `0` to override the guess.
+fn hyperlinks_supported() -> bool {
+    if let Some(force) = env::var_os("FORCE_HYPERLINK") {
+        return !force.is_empty() && force != "0";
+    }
+
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    if term == "dumb" {
+        return false;
+    }
+
+    // VTE based terminals support hyperlinks since version 0.50
+    let vte = var("VTE_VERSION").parse::<u32>().is_ok_and(|v| v >= 5000);
+    let program = matches!(
+        &var("TERM_PROGRAM")[..],
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+    );
+    let other = [
+        "WT_SESSION",
+        "KONSOLE_VERSION",
+        "KITTY_WINDOW_ID",
+        "DOMTERM",
+    ]
+    .iter()
+    .any(|name| env::var_os(name).is_some());
+    let term = ["kitty", "alacritty", "foot", "ghostty", "wezterm"]
+        .iter()
+        .any(|name| term.contains(name));
+    vte || program || other || term
 }
 
 impl Drop for Terminal {
@@ -43,19 +301,113 @@ impl Drop for Terminal {
 
 impl Terminal {
     /// Create a new [`Terminal`] that wraps stdout.
-    pub fn new() -> io::Result<Self> {
-        Self::with_target(Box::new(io::stdout()))
+    ///
+    /// Fails with [`Error::NotATty`] if stdout is not a terminal.
+    pub fn new() -> Result<Self, Error> {
+        Self::with_options(TerminalOptions::default())
+    }
+
+    /// Create a new [`Terminal`] that wraps stdout and enables the specified
+    /// terminal features.
+    pub fn with_options(options: TerminalOptions) -> Result<Self, Error> {
+        check_terminal(io::stdout().is_terminal())?;
+        Self::with_output(Box::new(io::stdout()), options, true)
+    }
+
+    /// Create a new [`Terminal`] that wraps stderr.
+    ///
+    /// This allows displaying a UI while stdout is redirected, for example
+    /// when the program's output is piped into another program.
+    pub fn stderr() -> Result<Self, Error> {
+        check_terminal(io::stderr().is_terminal())?;
+        Self::with_output(Box::new(io::stderr()), TerminalOptions::default(), true)
+    }
+
+    /// Create a new [`Terminal`] that writes directly to the controlling
+    /// terminal (`/dev/tty` on unix, `CONOUT$` on Windows).
+    ///
+    /// This allows displaying a UI while both stdout and stderr are
+    /// redirected.
+    pub fn tty() -> Result<Self, Error> {
+        #[cfg(not(windows))]
+        let path = "/dev/tty";
+        #[cfg(windows)]
+        let path = "CONOUT$";
+        let tty = OpenOptions::new().read(true).write(true).open(path)?;
+        check_terminal(tty.is_terminal())?;
+        Self::with_output(Box::new(tty), TerminalOptions::default(), true)
     }
 
     /// Create a new terminal wrapping a custom output.
-    pub fn with_target(out: Box<dyn Write>) -> io::Result<Self> {
+    ///
+    /// Unlike the other constructors, this doesn't check whether the output is
+    /// a terminal. Since the output may not reach the terminal, no queries are
+    /// written to it. Grapheme widths are estimated instead of measured, the
+    /// [`WidthEstimationMethod`] is guessed based on environment variables,
+    /// keyboard enhancement flags are not pushed and
+    /// [`Self::query_default_colors`] does nothing.
+    pub fn with_target(out: Box<dyn Write>) -> Result<Self, Error> {
+        Self::with_target_and_options(out, TerminalOptions::default())
+    }
+
+    /// Create a new terminal wrapping a custom output and enabling the
+    /// specified terminal features.
+    ///
+    /// For more details, see [`Self::with_target`].
+    pub fn with_target_and_options(
+        out: Box<dyn Write>,
+        options: TerminalOptions,
+    ) -> Result<Self, Error> {
+        Self::with_output(out, options, false)
+    }
+
+    fn with_output(
+        out: Box<dyn Write>,
+        options: TerminalOptions,
+        queries: bool,
+    ) -> Result<Self, Error> {
         let mut result = Self {
-            out,
+            out: Output::new(out),
             frame: Frame::default(),
             prev_frame_buffer: Buffer::default(),
             full_redraw: true,
+            hyperlinks: hyperlinks_supported(),
+            // Converting colors is opt-in, but plain text is always respected
+            color_depth: match ColorDepth::detect() {
+                ColorDepth::NoColor => ColorDepth::NoColor,
+                _ => ColorDepth::TrueColor,
+            },
+            options,
+            mouse_areas: vec![],
+            soft_cursor: false,
+            wheel_scroll: None,
+            max_fps: None,
+            last_present: None,
+            last_present_bytes: 0,
+            draw_duration: Duration::ZERO,
+            metrics: None,
+            prev_cursor_style: CursorStyle::Default,
+            prev_cursor_color: None,
+            prev_title: None,
+            notification_protocol: None,
+            default_fg: None,
+            default_bg: None,
+            keyboard_enhancement_supported: None,
+            keyboard_enhancement: KeyboardEnhancementFlags::empty(),
+            // The terminal features are enabled below
+            suspended: true,
+            queries,
+        };
+        let method = if queries {
+            WidthEstimationMethod::detect(&mut result.out)
+        } else {
+            WidthEstimationMethod::detect_from_env()
         };
+        result.frame.widthdb.set_estimation_method(method);
         result.unsuspend()?;
+        if options.query_default_colors && options.raw_mode {
+            result.query_default_colors(Duration::from_millis(100))?;
+        }
         Ok(result)
     }
 
@@ -67,40 +419,177 @@ impl Terminal {
     /// Call [`Self::unsuspend`] to return the terminal state before drawing and
     /// presenting the next frame.
     pub fn suspend(&mut self) -> io::Result<()> {
-        crossterm::terminal::disable_raw_mode()?;
-        #[cfg(not(windows))]
-        {
+        if self.options.raw_mode {
+            crossterm::terminal::disable_raw_mode()?;
+        }
+        if self.options.mouse_capture {
+            self.out.execute(DisableMouseCapture)?;
+        }
+        if self.options.focus_change {
+            self.out.execute(DisableFocusChange)?;
+        }
+        if !self.keyboard_enhancement.is_empty() {
             self.out.execute(PopKeyboardEnhancementFlags)?;
+            self.keyboard_enhancement = KeyboardEnhancementFlags::empty();
+        }
+        #[cfg(not(windows))]
+        if self.options.bracketed_paste {
             self.out.execute(DisableBracketedPaste)?;
         }
-        self.out.execute(LeaveAlternateScreen)?;
+        if self.options.alternate_screen {
+            self.out.execute(LeaveAlternateScreen)?;
+        }
+        if self.prev_cursor_style != CursorStyle::Default {
+            self.out.execute(SetCursorStyle::DefaultUserShape)?;
+            self.prev_cursor_style = CursorStyle::Default;
+        }
+        if self.prev_cursor_color.is_some() {
+            self.out.execute(SetCursorColor(None))?;
+            self.prev_cursor_color = None;
+        }
+        if self.options.restore_title {
+            self.out.execute(PopTitle)?;
+        }
+        if self.frame.widthdb.estimation_method() == WidthEstimationMethod::Unicode {
+            self.out.execute(SetGraphemeClusterMode(false))?;
+        }
         self.out.execute(Show)?;
+        self.suspended = true;
         Ok(())
     }
 
+    /// Run an external program that interacts with the user directly, for
+    /// example a text editor, pager or shell.
+    ///
+    /// The terminal is suspended while the program runs and unsuspended again
+    /// afterwards, even if the program could not be started. Afterwards, a new
+    /// frame needs to be drawn and presented by the application.
+    pub fn run_command(&mut self, command: &mut Command) -> io::Result<ExitStatus> {
+        self.suspend()?;
+        let status = command.status();
+        self.unsuspend()?;
+        status
+    }
+
+    /// Move the application to the background, like pressing Ctrl+Z in a
+    /// shell would.
+    ///
+    /// Since the terminal is in raw mode, pressing Ctrl+Z is reported as a key
+    /// event instead of suspending the process. Call this function in response
+    /// to restore the terminal state and stop the process using `SIGTSTP`. Once
+    /// the process is continued (e.g. via `fg`), the terminal is unsuspended
+    /// again and this function returns. A new frame then needs to be drawn and
+    /// presented by the application.
+    #[cfg(all(unix, feature = "job-control"))]
+    pub fn suspend_to_background(&mut self) -> io::Result<()> {
+        self.suspend()?;
+        signal_hook::low_level::raise(signal_hook::consts::SIGTSTP)?;
+        self.unsuspend()
+    }
+
     /// Restore the terminal state after calling [`Self::suspend`].
     ///
     /// After calling this function, a new frame needs to be drawn and presented
     /// by the application. The previous screen contents are **not** restored.
     pub fn unsuspend(&mut self) -> io::Result<()> {
-        crossterm::terminal::enable_raw_mode()?;
-        self.out.execute(EnterAlternateScreen)?;
+        if self.options.raw_mode {
+            crossterm::terminal::enable_raw_mode()?;
+        }
+        if self.options.alternate_screen {
+            self.out.execute(EnterAlternateScreen)?;
+        }
         #[cfg(not(windows))]
-        {
+        if self.options.bracketed_paste {
             self.out.execute(EnableBracketedPaste)?;
-            self.out.execute(PushKeyboardEnhancementFlags(
-                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES,
-            ))?;
+        }
+        let flags = self.options.keyboard_enhancement;
+        if !flags.is_empty() && self.keyboard_enhancement_supported() {
+            self.out.execute(PushKeyboardEnhancementFlags(flags))?;
+            self.keyboard_enhancement = flags;
+        }
+        if self.options.mouse_capture {
+            self.out.execute(EnableMouseCapture)?;
+        }
+        if self.options.focus_change {
+            self.out.execute(EnableFocusChange)?;
+        }
+        if self.options.restore_title {
+            self.out.execute(PushTitle)?;
+        }
+        if self.frame.widthdb.estimation_method() == WidthEstimationMethod::Unicode {
+            self.out.execute(SetGraphemeClusterMode(true))?;
+        }
+        // Other programs may have changed the title while we were suspended
+        if let Some(title) = &self.prev_title {
+            self.out.execute(SetTitle(title))?;
         }
         self.full_redraw = true;
+        self.suspended = false;
+        Ok(())
+    }
+
+    /// The terminal features enabled while the terminal is not suspended.
+    pub fn options(&self) -> TerminalOptions {
+        self.options
+    }
+
+    fn keyboard_enhancement_supported(&mut self) -> bool {
+        if !self.queries {
+            return false;
+        }
+        // Querying the terminal may take a while, so the result is cached
+        if let Some(supported) = self.keyboard_enhancement_supported {
+            return supported;
+        }
+        // Terminals that don't answer the query don't support it either
+        let supported = query::keyboard_enhancement_supported(&mut self.out).unwrap_or(false);
+        self.keyboard_enhancement_supported = Some(supported);
+        supported
+    }
+
+    /// Request different keyboard enhancement flags.
+    ///
+    /// For example, [`KeyboardEnhancementFlags::REPORT_EVENT_TYPES`] makes the
+    /// terminal report key repeat and release events, and
+    /// [`KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES`] makes it
+    /// report keys like enter or tab unambiguously. For more details, see
+    /// [`Self::keyboard_enhancement`].
+    ///
+    /// While the terminal is suspended, the flags are only recorded and
+    /// applied once it is unsuspended.
+    pub fn set_keyboard_enhancement(&mut self, flags: KeyboardEnhancementFlags) -> io::Result<()> {
+        self.options.keyboard_enhancement = flags;
+        if self.suspended {
+            return Ok(());
+        }
+        if !self.keyboard_enhancement.is_empty() {
+            self.out.execute(PopKeyboardEnhancementFlags)?;
+            self.keyboard_enhancement = KeyboardEnhancementFlags::empty();
+        }
+        if !flags.is_empty() && self.keyboard_enhancement_supported() {
+            self.out.execute(PushKeyboardEnhancementFlags(flags))?;
+            self.keyboard_enhancement = flags;
+        }
         Ok(())
     }
 
+    /// The keyboard enhancement flags that are currently applied.
+    ///
+    /// The flags requested via [`TerminalOptions::keyboard_enhancement`] or
+    /// [`Self::set_keyboard_enhancement`] are only applied if the terminal
+    /// supports keyboard enhancement, which is checked at runtime. For example,
+    /// if the flags are empty, key release events will never be reported. The
+    /// flags are always empty while the terminal is suspended.
+    pub fn keyboard_enhancement(&self) -> KeyboardEnhancementFlags {
+        self.keyboard_enhancement
+    }
+
     /// Set the tab width in columns.
     ///
     /// For more details, see [`Self::tab_width`].
     pub fn set_tab_width(&mut self, tab_width: u8) {
         self.frame.widthdb.tab_width = tab_width;
+        self.frame.widthdb.invalidate();
     }
 
     /// The tab width in columns.
@@ -112,11 +601,300 @@ impl Terminal {
         self.frame.widthdb.tab_width
     }
 
+    /// Enable or disable hyperlinks.
+    ///
+    /// For more details, see [`Self::hyperlinks`].
+    pub fn set_hyperlinks(&mut self, active: bool) {
+        self.hyperlinks = active;
+        self.full_redraw = true;
+    }
+
+    /// Whether hyperlinks should be displayed.
+    ///
+    /// When enabled, text that is part of a hyperlink (see
+    /// [`Frame::push_link`]) is surrounded by OSC 8 escape sequences, making it
+    /// clickable in terminals that support them. Most other terminals ignore
+    /// these sequences, but some may display them as garbage.
+    ///
+    /// When disabled, hyperlinks are displayed as plain text.
+    ///
+    /// Initially, hyperlinks are only enabled if environment variables like
+    /// `TERM_PROGRAM` or `VTE_VERSION` indicate that the terminal supports
+    /// them, or if `FORCE_HYPERLINK` is set to `1`.
+    pub fn hyperlinks(&self) -> bool {
+        self.hyperlinks
+    }
+
+    /// Set the colors the terminal is able to display.
+    ///
+    /// For more details, see [`Self::color_depth`].
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.color_depth = depth;
+        self.full_redraw = true;
+    }
+
+    /// The colors the terminal is able to display.
+    ///
+    /// Colors the terminal can't display are converted to the closest color it
+    /// can display, so that truecolor styles still look reasonable on basic
+    /// terminals. With [`ColorDepth::NoColor`], all styles and hyperlinks are
+    /// removed and only plain text is displayed.
+    ///
+    /// Initially, the color depth is [`ColorDepth::TrueColor`], so colors are
+    /// displayed unchanged, or [`ColorDepth::NoColor`] if
+    /// [`ColorDepth::detect`] returns it. To convert colors to the guessed
+    /// depth, use `set_color_depth(ColorDepth::detect())`.
+    pub fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    /// Ask the terminal for its default foreground and background colors.
+    ///
+    /// Waits at most `timeout` for the terminal to reply, since not all
+    /// terminals support this. The terminal must be in raw mode. Because the
+    /// reply arrives as terminal input, this should be called before the user
+    /// starts typing, for example right after creating the terminal (see
+    /// [`TerminalOptions::query_default_colors`]). Any other input read while
+    /// waiting for the reply is discarded.
+    ///
+    /// Afterwards, the colors are available via [`Self::default_foreground`]
+    /// and [`Self::default_background`].
+    pub fn query_default_colors(&mut self, timeout: Duration) -> io::Result<()> {
+        if !self.queries {
+            return Ok(());
+        }
+        self.out.queue(QueryColor(10))?.queue(QueryColor(11))?;
+        self.out.flush()?;
+
+        let deadline = Instant::now() + timeout;
+        let mut reply = String::new();
+        let (mut fg, mut bg) = (None, None);
+        while fg.is_none() || bg.is_none() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() || !crossterm::event::poll(remaining)? {
+                break;
+            }
+            let Some(input) = key_input(&crossterm::event::read()?) else {
+                break;
+            };
+            reply.push_str(&input);
+            fg = color::parse_color_reply(&reply, 10);
+            bg = color::parse_color_reply(&reply, 11);
+        }
+
+        self.default_fg = fg;
+        self.default_bg = bg;
+        Ok(())
+    }
+
+    /// The terminal's default foreground color, if known.
+    ///
+    /// For more details, see [`Self::query_default_colors`].
+    pub fn default_foreground(&self) -> Option<Color> {
+        let (r, g, b) = self.default_fg?;
+        Some(Color::Rgb { r, g, b })
+    }
+
+    /// The terminal's default background color, if known.
+    ///
+    /// For more details, see [`Self::query_default_colors`].
+    pub fn default_background(&self) -> Option<Color> {
+        let (r, g, b) = self.default_bg?;
+        Some(Color::Rgb { r, g, b })
+    }
+
+    /// Whether the terminal's default background color is dark, if known.
+    ///
+    /// Useful for picking a light or dark theme automatically.
+    pub fn dark_background(&self) -> Option<bool> {
+        self.default_bg.map(color::is_dark)
+    }
+
+    /// Enable or disable drawing the cursor as part of the frame.
+    ///
+    /// For more details, see [`Self::soft_cursor`].
+    pub fn set_soft_cursor(&mut self, active: bool) {
+        if active != self.soft_cursor {
+            self.soft_cursor = active;
+            self.full_redraw = true;
+        }
+    }
+
+    /// Whether the cursor is drawn as part of the frame instead of using the
+    /// terminal's cursor.
+    ///
+    /// When enabled, the colors of the cell under the cursor are reversed and
+    /// the terminal's cursor is hidden. It is still moved to the cursor
+    /// position though, so input methods and screen readers can follow it.
+    /// This is useful in terminals and multiplexers that don't display the
+    /// cursor reliably. Disabled by default.
+    pub fn soft_cursor(&self) -> bool {
+        self.soft_cursor
+    }
+
+    /// Enable or disable mouse capture.
+    ///
+    /// For more details, see [`Self::mouse_capture`].
+    pub fn set_mouse_capture(&mut self, active: bool) -> io::Result<()> {
+        if active != self.options.mouse_capture {
+            if active {
+                self.out.execute(EnableMouseCapture)?;
+            } else {
+                self.out.execute(DisableMouseCapture)?;
+            }
+            self.options.mouse_capture = active;
+        }
+        Ok(())
+    }
+
+    /// Whether the terminal should report mouse events.
+    ///
+    /// When enabled, clicks, drags and scroll wheel movements are reported as
+    /// [`crossterm::event::Event::Mouse`]. Most terminals don't allow the user
+    /// to select text normally while mouse capture is enabled. Disabled by
+    /// default.
+    ///
+    /// To find out which widget an event is meant for, see
+    /// [`Self::mouse_target`] and [`Self::scroll_target`].
+    pub fn mouse_capture(&self) -> bool {
+        self.options.mouse_capture
+    }
+
+    /// The mouse areas registered while drawing the previously presented frame.
+    ///
+    /// For more details, see [`Frame::register_mouse_area`].
+    pub fn mouse_areas(&self) -> &[MouseArea] {
+        &self.mouse_areas
+    }
+
+    /// Find the topmost mouse area a mouse event happened in.
+    ///
+    /// Returns the id of the area and the position of the event relative to
+    /// the area. For more details, see [`Frame::register_mouse_area`].
+    pub fn mouse_target(&self, event: &MouseEvent) -> Option<(&str, Pos)> {
+        self.mouse_areas
+            .iter()
+            .rev()
+            .find_map(|area| Some((&area.id as &str, area.event_pos(event)?)))
+    }
+
+    /// Set how many lines to scroll per scroll wheel event, or disable
+    /// translating scroll wheel events.
+    ///
+    /// For more details, see [`Self::scroll_target`].
+    pub fn set_wheel_scroll(&mut self, lines: Option<u16>) {
+        self.wheel_scroll = lines;
+    }
+
+    /// How many lines to scroll per scroll wheel event, if scroll wheel events
+    /// are translated at all. Disabled by default.
+    pub fn wheel_scroll(&self) -> Option<u16> {
+        self.wheel_scroll
+    }
+
+    /// Translate a scroll wheel event into a [`WheelScroll`] for the topmost mouse
+    /// area under the pointer.
+    ///
+    /// The amount of lines to scroll is determined by [`Self::wheel_scroll`].
+    /// Returns [`None`] if translating scroll wheel events is disabled, the
+    /// event is not a scroll wheel event, or there is no mouse area under the
+    /// pointer.
+    pub fn scroll_target(&self, event: &MouseEvent) -> Option<WheelScroll<'_>> {
+        let lines: i32 = self.wheel_scroll?.into();
+        let (dx, dy) = mouse::wheel_direction(event)?;
+        let (id, pos) = self.mouse_target(event)?;
+        Some(WheelScroll {
+            id,
+            pos,
+            dx: dx * lines,
+            dy: dy * lines,
+        })
+    }
+
+    /// Set how desktop notifications should be shown.
+    ///
+    /// For more details, see [`Self::notification_protocol`].
+    pub fn set_notification_protocol(&mut self, protocol: Option<NotificationProtocol>) {
+        self.notification_protocol = protocol;
+    }
+
+    /// The escape sequence used to show notifications sent via
+    /// [`Frame::notify`].
+    ///
+    /// Since there is no reliable way to detect which protocol the terminal
+    /// supports, notifications are disabled by default.
+    pub fn notification_protocol(&self) -> Option<NotificationProtocol> {
+        self.notification_protocol
+    }
+
+    /// Set whether the terminal window currently has focus.
+    ///
+    /// For more details, see [`Self::focused`].
+    pub fn set_focused(&mut self, focused: bool) {
+        self.frame.focused = focused;
+    }
+
+    /// Whether the terminal window currently has focus.
+    ///
+    /// The terminal only reports focus changes if
+    /// [`TerminalOptions::focus_change`] is enabled. The focus state is then
+    /// updated by passing every event to [`Self::track_focus`], which
+    /// [`Self::run`] does automatically. Widgets can access the focus state via
+    /// [`Frame::focused`].
+    ///
+    /// Initially, the terminal is assumed to have focus.
+    pub fn focused(&self) -> bool {
+        self.frame.focused
+    }
+
+    /// Update the focus state if the event is a focus change.
+    ///
+    /// For more details, see [`Self::focused`].
+    pub fn track_focus(&mut self, event: &Event) {
+        match event {
+            Event::FocusGained => self.set_focused(true),
+            Event::FocusLost => self.set_focused(false),
+            _ => {}
+        }
+    }
+
+    /// Limit how many frames are presented per second.
+    ///
+    /// For more details, see [`Self::max_fps`].
+    pub fn set_max_fps(&mut self, fps: Option<u32>) {
+        assert_ne!(fps, Some(0));
+        self.max_fps = fps;
+    }
+
+    /// The maximum amount of frames presented per second, if limited.
+    ///
+    /// When limited, [`Self::present`] waits until enough time has passed since
+    /// the previous frame was presented, while [`Self::present_if_due`] skips
+    /// frames that are presented too early. This is useful when frames are
+    /// redrawn in response to high-frequency events, for example when running
+    /// a command and displaying its output line by line.
+    ///
+    /// Unlimited by default.
+    pub fn max_fps(&self) -> Option<u32> {
+        self.max_fps
+    }
+
+    /// How long to wait until the next frame may be presented according to
+    /// [`Self::max_fps`].
+    pub fn time_until_next_frame(&self) -> Duration {
+        let (Some(fps), Some(last_present)) = (self.max_fps, self.last_present) else {
+            return Duration::ZERO;
+        };
+        let frame_time = Duration::from_secs(1) / fps;
+        frame_time.saturating_sub(last_present.elapsed())
+    }
+
     /// Enable or disable grapheme width measurements.
     ///
     /// For more details, see [`Self::measuring`].
     pub fn set_measuring(&mut self, active: bool) {
         self.frame.widthdb.active = active;
+        self.frame.widthdb.invalidate();
     }
 
     /// Whether grapheme widths should be measured or estimated.
@@ -125,25 +903,47 @@ impl Terminal {
     /// terminal emulator, and may even depend on the font the user is using.
     ///
     /// When enabled, any newly encountered graphemes are measured whenever
-    /// [`Self::measure_widths`] is called. This is done by clearing the screen,
-    /// printing the grapheme and measuring the resulting cursor position.
-    /// Because of this, the screen will flicker occasionally. However, grapheme
-    /// widths will always be accurate independent of the terminal
-    /// configuration.
-    ///
-    /// When disabled, the width of graphemes is estimated using the Unicode
-    /// Standard Annex #11. This usually works fine, but may break on some emoji
-    /// or other less commonly used character sequences.
+    /// [`Self::measure_widths`] is called. This is done by printing the
+    /// grapheme in the bottom row and measuring the resulting cursor position.
+    /// Because of this, the bottom row may flicker occasionally in terminals
+    /// that don't support synchronized updates. However, grapheme widths will
+    /// always be accurate independent of the terminal configuration.
+    ///
+    /// When disabled, the width of graphemes is estimated (see
+    /// [`WidthEstimationMethod`]). This usually works fine, but may break on
+    /// some emoji or other less commonly used character sequences.
     pub fn measuring(&self) -> bool {
         self.frame.widthdb.active
     }
 
+    /// Set how grapheme widths are estimated.
+    ///
+    /// For more details, see [`Self::width_estimation_method`].
+    pub fn set_width_estimation_method(&mut self, method: WidthEstimationMethod) -> io::Result<()> {
+        let previous = self.frame.widthdb.estimation_method();
+        if method != previous {
+            let unicode = method == WidthEstimationMethod::Unicode;
+            self.out.execute(SetGraphemeClusterMode(unicode))?;
+            self.frame.widthdb.set_estimation_method(method);
+        }
+        Ok(())
+    }
+
+    /// How grapheme widths are estimated when they are not measured.
+    ///
+    /// By default, the method is chosen using [`WidthEstimationMethod::detect`].
+    /// If [`WidthEstimationMethod::Unicode`] is used, mode 2027 is enabled so
+    /// terminals supporting it treat grapheme clusters as a unit.
+    pub fn width_estimation_method(&self) -> WidthEstimationMethod {
+        self.frame.widthdb.estimation_method()
+    }
+
     /// Whether any unmeasured graphemes were seen since the last call to
     /// [`Self::measure_widths`].
     ///
     /// Returns `true` whenever [`Self::measure_widths`] would return `true`.
     pub fn measuring_required(&self) -> bool {
-        self.frame.widthdb.measuring_required()
+        self.queries && self.frame.widthdb.measuring_required()
     }
 
     /// Measure widths of all unmeasured graphemes.
@@ -155,10 +955,32 @@ impl Terminal {
     /// redrawn. Keep in mind that after redrawing the screen, graphemes may
     /// have become visible that have not yet been measured. You should keep
     /// re-measuring and re-drawing until this function returns `false`.
+    ///
+    /// The terminal's replies are read directly from the controlling terminal.
+    /// While another thread waits for input using crossterm, for example an
+    /// `events::Events` stream, the replies may be read by that thread
+    /// instead and measuring fails after a timeout. Only measure while no
+    /// events are being awaited.
     pub fn measure_widths(&mut self) -> io::Result<bool> {
-        if self.frame.widthdb.measuring_required() {
-            self.full_redraw = true;
-            self.frame.widthdb.measure_widths(&mut self.out)?;
+        if !self.queries {
+            // The graphemes can't be measured, so their widths stay estimated
+            self.frame.widthdb.discard_requests();
+            Ok(false)
+        } else if self.frame.widthdb.measuring_required() {
+            // Graphemes are measured in the bottom row, which is cleared
+            // afterwards. The rest of the screen stays intact, so only that row
+            // needs to be redrawn.
+            let Size { width, height } = self.prev_frame_buffer.size();
+            let row = height.saturating_sub(1);
+            let width = width.max(1);
+            self.frame
+                .widthdb
+                .measure_widths(&mut self.out, row, width)?;
+            if height > 0 {
+                self.prev_frame_buffer.clear_row(row);
+            } else {
+                self.full_redraw = true;
+            }
             Ok(true)
         } else {
             Ok(false)
@@ -172,9 +994,11 @@ impl Terminal {
     /// [`Self::present`]. It is not necessary to call this when using
     /// [`Self::present_widget`] or [`Self::present_async_widget`].
     pub fn autoresize(&mut self) -> io::Result<()> {
-        let (width, height) = crossterm::terminal::size()?;
-        let size = Size { width, height };
+        let size = self.size()?;
         if size != self.frame.size() {
+            if self.frame.size() != Size::ZERO {
+                self.out.record_resize(size)?;
+            }
             self.frame.buffer.resize(size);
             self.prev_frame_buffer.resize(size);
             self.full_redraw = true;
@@ -183,11 +1007,91 @@ impl Terminal {
         Ok(())
     }
 
+    /// The current size of the terminal.
+    ///
+    /// Unlike [`Self::autoresize`], this doesn't modify the current frame.
+    pub fn size(&self) -> io::Result<Size> {
+        let (width, height) = crossterm::terminal::size()?;
+        Ok(Size { width, height })
+    }
+
+    /// Whether the terminal size differs from the size of the previously
+    /// presented frame.
+    ///
+    /// This can be used to decide whether a new frame needs to be drawn, for
+    /// example after a burst of events that may have included resizes.
+    pub fn size_changed_since_present(&self) -> io::Result<bool> {
+        Ok(self.size()? != self.prev_frame_buffer.size())
+    }
+
     /// The current frame.
     pub fn frame(&mut self) -> &mut Frame {
         &mut self.frame
     }
 
+    /// Start recording everything displayed on the terminal.
+    ///
+    /// The recording is written to `out` in the asciinema v2 cast format and
+    /// can be played back using `asciinema play`. Each presented frame is
+    /// recorded as a single event. The next frame is fully redrawn so the
+    /// recording starts with the complete screen contents.
+    pub fn start_recording(&mut self, out: Box<dyn Write>) -> io::Result<()> {
+        let size = self.size()?;
+        self.out.start_recording(out, size)?;
+        self.full_redraw = true;
+        Ok(())
+    }
+
+    /// Stop recording and return the output the recording was written to, if
+    /// a recording was in progress.
+    pub fn stop_recording(&mut self) -> io::Result<Option<Box<dyn Write>>> {
+        self.out.stop_recording()
+    }
+
+    /// Whether the terminal is currently being recorded.
+    ///
+    /// For more details, see [`Self::start_recording`].
+    pub fn recording(&self) -> bool {
+        self.out.is_recording()
+    }
+
+    /// The amount of bytes written to the terminal to display the most
+    /// recently presented frame.
+    ///
+    /// All output is buffered and written at once when a frame is presented,
+    /// so this is the size of that single write. Useful for diagnosing slow
+    /// updates, e.g. over SSH.
+    pub fn last_present_bytes(&self) -> usize {
+        self.last_present_bytes
+    }
+
+    /// Enable or disable collecting [`FrameMetrics`].
+    ///
+    /// For more details, see [`Self::metrics`].
+    pub fn set_metrics(&mut self, active: bool) {
+        if active != self.metrics.is_some() {
+            self.metrics = active.then(FrameMetrics::default);
+        }
+    }
+
+    /// Statistics about the most recently presented frame, or [`None`] if
+    /// collecting metrics is disabled.
+    ///
+    /// Useful for finding out which part of presenting a frame is slow.
+    /// Disabled by default.
+    pub fn metrics(&self) -> Option<FrameMetrics> {
+        self.metrics
+    }
+
+    /// The contents of the most recently presented frame.
+    ///
+    /// This is what the terminal should currently be displaying, unless the
+    /// terminal was resized since. Useful for tests and debug logging, e.g.
+    /// via [`Buffer::to_plain_string`].
+    pub fn last_frame(&self) -> &Buffer {
+        &self.prev_frame_buffer
+    }
+
     /// A database of grapheme widths.
     pub fn widthdb(&mut self) -> &mut WidthDb {
         &mut self.frame.widthdb
@@ -199,6 +1103,26 @@ impl Terminal {
         self.full_redraw = true;
     }
 
+    /// Copy text to the system clipboard using OSC 52.
+    ///
+    /// Whether this works depends on the terminal.
+    pub fn set_clipboard(&mut self, text: &str) -> io::Result<()> {
+        self.out.execute(SetClipboard(text))?;
+        Ok(())
+    }
+
+    /// Send text copied to an [`Osc52Clipboard`] to the terminal, if there is
+    /// any.
+    ///
+    /// This should be called after every operation that may have copied text,
+    /// for example once per event.
+    pub fn sync_clipboard(&mut self, clipboard: &mut Osc52Clipboard) -> io::Result<()> {
+        if let Some(text) = clipboard.take_pending() {
+            self.set_clipboard(text)?;
+        }
+        Ok(())
+    }
+
     /// Display the current frame on the screen and prepare the next frame.
     ///
     /// Before drawing and presenting a frame, [`Self::measure_widths`] and
@@ -206,16 +1130,57 @@ impl Terminal {
     ///
     /// After calling this function, the frame returned by [`Self::frame`] will
     /// be empty again and have no cursor position.
+    ///
+    /// If the frame rate is limited (see [`Self::max_fps`]), this function
+    /// blocks until the frame may be presented.
     pub fn present(&mut self) -> io::Result<()> {
+        thread::sleep(self.time_until_next_frame());
+        self.present_now()
+    }
+
+    /// Like [`Self::present`], but if the frame rate is limited (see
+    /// [`Self::max_fps`]) and the frame is not due yet, the frame is discarded
+    /// instead of waiting.
+    ///
+    /// Returns `true` if the frame was presented. If it was discarded, the
+    /// application should redraw and present it again once
+    /// [`Self::time_until_next_frame`] has passed.
+    pub fn present_if_due(&mut self) -> io::Result<bool> {
+        if self.time_until_next_frame().is_zero() {
+            self.present_now()?;
+            Ok(true)
+        } else {
+            self.frame.reset();
+            Ok(false)
+        }
+    }
+
+    fn present_now(&mut self) -> io::Result<()> {
+        let start = Instant::now();
         self.out.queue(BeginSynchronizedUpdate)?;
         let result = self.draw_to_screen();
         self.out.queue(EndSynchronizedUpdate)?;
-        result?;
+        let cells_changed = result?;
 
+        let diffed = Instant::now();
         self.out.flush()?;
+        self.last_present_bytes = self.out.last_flush_len();
+
+        let draw = mem::take(&mut self.draw_duration);
+        if let Some(metrics) = &mut self.metrics {
+            *metrics = FrameMetrics {
+                draw,
+                diff: diffed - start,
+                write: diffed.elapsed(),
+                cells_changed,
+                bytes_written: self.last_present_bytes,
+            };
+        }
 
         mem::swap(&mut self.prev_frame_buffer, &mut self.frame.buffer);
+        self.mouse_areas = mem::take(&mut self.frame.mouse_areas);
         self.frame.reset();
+        self.last_present = Some(Instant::now());
 
         Ok(())
     }
@@ -230,7 +1195,9 @@ impl Terminal {
         W: Widget<E>,
     {
         self.autoresize()?;
+        let start = Instant::now();
         widget.draw(self.frame())?;
+        self.draw_duration = start.elapsed();
         self.present()?;
         Ok(())
     }
@@ -245,48 +1212,248 @@ impl Terminal {
         W: AsyncWidget<E>,
     {
         self.autoresize()?;
+        let start = Instant::now();
         widget.draw(self.frame()).await?;
+        self.draw_duration = start.elapsed();
         self.present()?;
         Ok(())
     }
 
-    fn draw_to_screen(&mut self) -> io::Result<()> {
+    /// Display a [`Widget`] on the screen, measuring any new graphemes and
+    /// redrawing the widget until all visible graphemes have been measured.
+    ///
+    /// This replaces the usual loop of calling [`Self::present_widget`] and
+    /// [`Self::measure_widths`] until the latter returns `false`. Because the
+    /// widget may need to be drawn multiple times, it is created by `widget`.
+    pub fn present_widget_measured<E, W, F>(&mut self, mut widget: F) -> Result<(), E>
+    where
+        E: From<io::Error>,
+        W: Widget<E>,
+        F: FnMut() -> W,
+    {
+        loop {
+            self.present_widget(widget())?;
+            if !self.measure_widths()? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Like [`Self::present_widget_measured`], but for [`AsyncWidget`]s.
+    pub async fn present_async_widget_measured<E, W, F>(&mut self, mut widget: F) -> Result<(), E>
+    where
+        E: From<io::Error>,
+        W: AsyncWidget<E>,
+        F: FnMut() -> W,
+    {
+        loop {
+            self.present_async_widget(widget()).await?;
+            if !self.measure_widths()? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Run an application until its event handler breaks.
+    ///
+    /// Each iteration, the screen is redrawn using `draw` until all visible
+    /// graphemes have been measured (see [`Self::measure_widths`]). Then, the
+    /// next event is read and passed to `handle`. Both functions have access
+    /// to the application's `state`. Focus changes are tracked automatically
+    /// (see [`Self::focused`]).
+    ///
+    /// Usually, `draw` creates a widget and draws it to the frame, for example
+    /// `|state, frame| state.widget().draw(frame)`.
+    pub fn run<S, E, D, H>(&mut self, state: &mut S, mut draw: D, mut handle: H) -> Result<(), E>
+    where
+        E: From<io::Error>,
+        D: FnMut(&mut S, &mut Frame) -> Result<(), E>,
+        H: FnMut(&mut S, &mut Self, Event) -> Result<ControlFlow<()>, E>,
+    {
+        loop {
+            loop {
+                self.autoresize()?;
+                let start = Instant::now();
+                draw(state, self.frame())?;
+                self.draw_duration = start.elapsed();
+                self.present()?;
+                if !self.measure_widths()? {
+                    break;
+                }
+            }
+
+            let event = crossterm::event::read()?;
+            self.track_focus(&event);
+            if handle(state, self, event)?.is_break() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Like [`Self::run`], but `draw` returns a future.
+    ///
+    /// Usually, `draw` creates an [`AsyncWidget`] and draws it to the frame,
+    /// for example `|state, frame| Box::pin(state.widget().draw(frame))`.
+    ///
+    /// Events are read using [`crate::events::Events`] and the frame rate is
+    /// limited using [`tokio::time::sleep`], so the executor is not blocked
+    /// while waiting. Must be used within a tokio runtime with the time driver
+    /// enabled.
+    #[cfg(feature = "events")]
+    pub async fn run_async<S, E, D, H>(
+        &mut self,
+        state: &mut S,
+        mut draw: D,
+        mut handle: H,
+    ) -> Result<(), E>
+    where
+        E: From<io::Error>,
+        D: for<'a> FnMut(&'a mut S, &'a mut Frame) -> DrawFuture<'a, E>,
+        H: FnMut(&mut S, &mut Self, Event) -> Result<ControlFlow<()>, E>,
+    {
+        let mut events = crate::events::Events::new();
+        loop {
+            loop {
+                self.autoresize()?;
+                let start = Instant::now();
+                draw(state, self.frame()).await?;
+                self.draw_duration = start.elapsed();
+                tokio::time::sleep(self.time_until_next_frame()).await;
+                self.present_now()?;
+                if !self.measure_widths()? {
+                    break;
+                }
+            }
+
+            // Neither ticks nor timers are enabled, so only terminal events
+            // are produced
+            let event = match events.next().await {
+                Some(Ok(crate::events::Event::Terminal(event))) => event,
+                Some(Ok(_)) => continue,
+                Some(Err(err)) => return Err(err.into()),
+                None => return Ok(()),
+            };
+            self.track_focus(&event);
+            if handle(state, self, event)?.is_break() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns the amount of cells that changed.
+    fn draw_to_screen(&mut self) -> io::Result<usize> {
         if self.full_redraw {
             self.out.queue(Clear(ClearType::All))?;
             self.prev_frame_buffer.reset(); // Because the screen is now empty
             self.full_redraw = false;
         }
 
-        self.draw_differences()?;
+        if self.soft_cursor {
+            self.frame.buffer.draw_soft_cursor();
+        }
+        let cells_changed = self.draw_differences()?;
         self.update_cursor()?;
         self.update_title()?;
+        self.send_alerts()?;
 
-        Ok(())
+        Ok(cells_changed)
     }
 
-    fn draw_differences(&mut self) -> io::Result<()> {
-        for (x, y, cell) in self.frame.buffer.cells() {
+    /// Returns the amount of cells that changed.
+    fn draw_differences(&mut self) -> io::Result<usize> {
+        let width = self.frame.buffer.size().width;
+        let links = self.hyperlinks && self.color_depth != ColorDepth::NoColor;
+
+        // State of the terminal after the previous cell was printed. Every
+        // update ends with the style and hyperlink being reset.
+        let mut cursor = None;
+        let mut style = ContentStyle::default();
+        let mut link = None;
+        let mut changed = 0;
+
+        // Rows that weren't written to in either frame are empty in both and
+        // can be skipped entirely.
+        let rows = (0..self.frame.buffer.size().height).filter(|&y| {
+            self.frame.buffer.is_row_dirty(y) || self.prev_frame_buffer.is_row_dirty(y)
+        });
+        let cells = rows.flat_map(|y| self.frame.buffer.row_cells(y));
+
+        for (x, y, cell) in cells {
             if self.prev_frame_buffer.at(x, y) == cell {
                 continue;
             }
+            changed += 1;
+
+            if cursor != Some((x, y)) {
+                self.out.queue(MoveTo(x, y))?;
+            }
 
-            let content = StyledContent::new(cell.style, &cell.content as &str);
-            self.out
-                .queue(MoveTo(x, y))?
-                .queue(PrintStyledContent(content))?;
+            let cell_style = self.color_depth.convert_style(cell.style);
+            if cell_style != style {
+                self.out
+                    .queue(SetAttribute(Attribute::Reset))?
+                    .queue(SetStyle(cell_style))?;
+                style = cell_style;
+            }
+
+            let cell_link = cell.link.as_ref().filter(|_| links);
+            if cell_link != link {
+                self.out.queue(SetHyperlink(cell_link.map(|l| l as &str)))?;
+                link = cell_link;
+            }
+
+            self.out.queue(Print(&cell.content as &str))?;
+
+            // In the last column, terminals don't agree on where the cursor
+            // ends up after printing.
+            let next_x = x + cell.width as u16;
+            cursor = (next_x < width).then_some((next_x, y));
         }
-        Ok(())
+
+        if style != ContentStyle::default() {
+            self.out.queue(SetAttribute(Attribute::Reset))?;
+        }
+        if link.is_some() {
+            self.out.queue(SetHyperlink(None))?;
+        }
+        Ok(changed)
     }
 
     fn update_cursor(&mut self) -> io::Result<()> {
+        let style = self.frame.cursor_style();
+        if style != self.prev_cursor_style {
+            self.out.queue(match style {
+                CursorStyle::Default => SetCursorStyle::DefaultUserShape,
+                CursorStyle::BlinkingBlock => SetCursorStyle::BlinkingBlock,
+                CursorStyle::SteadyBlock => SetCursorStyle::SteadyBlock,
+                CursorStyle::BlinkingUnderline => SetCursorStyle::BlinkingUnderScore,
+                CursorStyle::SteadyUnderline => SetCursorStyle::SteadyUnderScore,
+                CursorStyle::BlinkingBar => SetCursorStyle::BlinkingBar,
+                CursorStyle::SteadyBar => SetCursorStyle::SteadyBar,
+            })?;
+            self.prev_cursor_style = style;
+        }
+
+        let color = match self.color_depth {
+            ColorDepth::NoColor => None,
+            _ => self.frame.cursor_color().and_then(color::to_rgb),
+        };
+        if color != self.prev_cursor_color {
+            self.out.queue(SetCursorColor(color))?;
+            self.prev_cursor_color = color;
+        }
+
         if let Some(pos) = self.frame.cursor() {
             let size = self.frame.size();
             let x_in_bounds = 0 <= pos.x && pos.x < size.width as i32;
             let y_in_bounds = 0 <= pos.y && pos.y < size.height as i32;
             if x_in_bounds && y_in_bounds {
-                self.out
-                    .queue(Show)?
-                    .queue(MoveTo(pos.x as u16, pos.y as u16))?;
+                if self.soft_cursor {
+                    self.out.queue(Hide)?;
+                } else {
+                    self.out.queue(Show)?;
+                }
+                self.out.queue(MoveTo(pos.x as u16, pos.y as u16))?;
                 return Ok(());
             }
         }
@@ -295,9 +1462,28 @@ impl Terminal {
         Ok(())
     }
 
+    fn send_alerts(&mut self) -> io::Result<()> {
+        if self.frame.bell {
+            self.out.queue(RingBell)?;
+        }
+        if let Some(protocol) = self.notification_protocol {
+            for notification in &self.frame.notifications {
+                self.out.queue(Notify {
+                    protocol,
+                    title: &notification.title,
+                    body: &notification.body,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
     fn update_title(&mut self) -> io::Result<()> {
         if let Some(title) = &self.frame.title {
-            self.out.queue(SetTitle(title.clone()))?;
+            if self.prev_title.as_ref() != Some(title) {
+                self.out.queue(SetTitle(title))?;
+                self.prev_title = Some(title.clone());
+            }
         }
         Ok(())
     }
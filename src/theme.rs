@@ -0,0 +1,45 @@
+//! Styles shared between widgets.
+
+use crossterm::style::Stylize;
+
+use crate::Style;
+
+/// A palette of styles for semantic roles.
+///
+/// The default theme contains the default styles of the built-in widgets.
+/// Widgets using the theme provide a `with_theme` function that applies the
+/// styles of all roles relevant to them.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Highlighted or important elements.
+    pub accent: Style,
+    /// Error messages and invalid input.
+    pub error: Style,
+    /// Less important elements like line numbers.
+    pub dim: Style,
+    /// Selected text.
+    pub selection: Style,
+    /// Borders around widgets.
+    pub border: Style,
+    /// Placeholders shown instead of actual content.
+    pub placeholder: Style,
+    /// Popup menus.
+    pub menu: Style,
+    /// The selected entry of a popup menu.
+    pub menu_selected: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: Style::new().blue().bold(),
+            error: Style::new().red(),
+            dim: Style::new().dark_grey(),
+            selection: Style::new().reverse(),
+            border: Style::new(),
+            placeholder: Style::new().grey().italic(),
+            menu: Style::new().black().on_grey().opaque(),
+            menu_selected: Style::new().white().on_dark_blue().opaque(),
+        }
+    }
+}
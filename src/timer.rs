@@ -0,0 +1,117 @@
+//! Scheduling one-shot and recurring timers.
+
+use std::time::{Duration, Instant};
+
+/// Identifies a timer added to [`Timers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+#[derive(Debug)]
+struct Timer {
+    id: TimerId,
+    deadline: Instant,
+    /// Recurring timers are rescheduled after they expire.
+    period: Option<Duration>,
+}
+
+/// A collection of one-shot and recurring timers.
+///
+/// Timers are useful as a time source for animations like spinners, or for
+/// removing notifications after a while. The timers don't wake anything up by
+/// themselves. Instead, [`Self::time_until_next`] can be used as the timeout
+/// when waiting for the next terminal event (e.g. using
+/// [`crossterm::event::poll`]), after which expired timers can be collected
+/// using [`Self::pop_expired`]. With the `events` feature, the `events::Events`
+/// stream does this automatically.
+#[derive(Debug, Default)]
+pub struct Timers {
+    next_id: u64,
+    timers: Vec<Timer>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add(&mut self, delay: Duration, period: Option<Duration>) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        let deadline = Instant::now() + delay;
+        self.timers.push(Timer {
+            id,
+            deadline,
+            period,
+        });
+        id
+    }
+
+    /// Add a timer that expires once after `delay` has passed.
+    pub fn add_timeout(&mut self, delay: Duration) -> TimerId {
+        self.add(delay, None)
+    }
+
+    /// Add a timer that expires every time `period` has passed, starting
+    /// `period` from now.
+    ///
+    /// If expired timers are not collected in time, missed expirations are
+    /// skipped instead of being reported in a burst.
+    pub fn add_interval(&mut self, period: Duration) -> TimerId {
+        assert!(!period.is_zero());
+        self.add(period, Some(period))
+    }
+
+    /// Remove a timer.
+    ///
+    /// Returns `true` if the timer existed.
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|t| t.id != id);
+        self.timers.len() != len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// When the next timer expires, if there are any timers.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|t| t.deadline).min()
+    }
+
+    /// How long until the next timer expires, if there are any timers.
+    ///
+    /// Returns [`Duration::ZERO`] if a timer has already expired.
+    pub fn time_until_next(&self) -> Option<Duration> {
+        let deadline = self.next_deadline()?;
+        Some(deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Remove and return the timer that expired first, if any timers have
+    /// expired.
+    ///
+    /// One-shot timers are removed while recurring timers are rescheduled.
+    pub fn pop_expired(&mut self) -> Option<TimerId> {
+        let now = Instant::now();
+        let (i, timer) = self
+            .timers
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, t)| t.deadline <= now)
+            .min_by_key(|(_, t)| t.deadline)?;
+
+        let id = timer.id;
+        match timer.period {
+            Some(period) => {
+                timer.deadline += period;
+                if timer.deadline <= now {
+                    timer.deadline = now + period;
+                }
+            }
+            None => {
+                self.timers.remove(i);
+            }
+        }
+        Some(id)
+    }
+}
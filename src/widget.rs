@@ -1,8 +1,8 @@
 use async_trait::async_trait;
 
 use crate::widgets::{
-    Background, Border, Boxed, BoxedAsync, BoxedSendSync, Desync, Either2, Either3, Float,
-    JoinSegment, Layer2, Padding, Resize, Title,
+    Background, Border, Boxed, BoxedAsync, BoxedSendSync, Desync, Either2, Either3, Float, Gutter,
+    Hyperlink, JoinSegment, Layer2, Padding, Resize, Skeleton, Title,
 };
 use crate::{Frame, Size, WidthDb};
 
@@ -89,6 +89,14 @@ pub trait WidgetExt: Sized {
         Float::new(self)
     }
 
+    fn gutter(self) -> Gutter<Self> {
+        Gutter::new(self)
+    }
+
+    fn hyperlink<S: ToString>(self, url: S) -> Hyperlink<Self> {
+        Hyperlink::new(self, url)
+    }
+
     fn segment(self) -> JoinSegment<Self> {
         JoinSegment::new(self)
     }
@@ -109,6 +117,10 @@ pub trait WidgetExt: Sized {
         Resize::new(self)
     }
 
+    fn skeleton(self) -> Skeleton<Self> {
+        Skeleton::new(self)
+    }
+
     fn title<S: ToString>(self, title: S) -> Title<Self> {
         Title::new(self, title)
     }
@@ -1,33 +1,55 @@
 pub mod background;
 pub mod border;
 pub mod boxed;
+pub mod clock;
+pub mod columns;
+pub mod completion_menu;
 pub mod cursor;
 pub mod desync;
 pub mod editor;
 pub mod either;
 pub mod empty;
 pub mod float;
+pub mod gutter;
+pub mod hyperlink;
 pub mod join;
 pub mod layer;
+pub mod lines;
+pub mod marquee;
+pub mod masonry;
 pub mod padding;
 pub mod predrawn;
 pub mod resize;
+pub mod scroll;
+pub mod segmented_bar;
+pub mod skeleton;
 pub mod text;
 pub mod title;
 
 pub use background::*;
 pub use border::*;
 pub use boxed::*;
+pub use clock::*;
+pub use columns::*;
+pub use completion_menu::*;
 pub use cursor::*;
 pub use desync::*;
 pub use editor::*;
 pub use either::*;
 pub use empty::*;
 pub use float::*;
+pub use gutter::*;
+pub use hyperlink::*;
 pub use join::*;
 pub use layer::*;
+pub use lines::*;
+pub use marquee::*;
+pub use masonry::*;
 pub use padding::*;
 pub use predrawn::*;
 pub use resize::*;
+pub use scroll::*;
+pub use segmented_bar::*;
+pub use skeleton::*;
 pub use text::*;
 pub use title::*;
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-use crate::{AsyncWidget, Frame, Pos, Size, Style, Widget, WidthDb};
+use crate::{AsyncWidget, Frame, Pos, Size, Style, Theme, Widget, WidthDb};
 
 #[derive(Debug, Clone, Copy)]
 pub struct BorderLook {
@@ -98,7 +98,7 @@ impl<I> Border<I> {
         Self {
             inner,
             look: BorderLook::default(),
-            style: Style::default(),
+            style: Theme::default().border,
         }
     }
 
@@ -112,6 +112,10 @@ impl<I> Border<I> {
         self
     }
 
+    pub fn with_theme(self, theme: &Theme) -> Self {
+        self.with_style(theme.border)
+    }
+
     fn draw_border(&self, frame: &mut Frame) {
         let size = frame.size();
         let right = size.width.saturating_sub(1).into();
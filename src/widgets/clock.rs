@@ -0,0 +1,159 @@
+use std::fmt::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Frame, Pos, Size, Style, Widget, WidthDb};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The three rows of a character in a seven-segment display.
+fn seven_segment(c: char) -> [&'static str; 3] {
+    match c {
+        '0' => [" _ ", "| |", "|_|"],
+        '1' => ["   ", "  |", "  |"],
+        '2' => [" _ ", " _|", "|_ "],
+        '3' => [" _ ", " _|", " _|"],
+        '4' => ["   ", "|_|", "  |"],
+        '5' => [" _ ", "|_ ", " _|"],
+        '6' => [" _ ", "|_ ", "|_|"],
+        '7' => [" _ ", "  |", "  |"],
+        '8' => [" _ ", "|_|", "|_|"],
+        '9' => [" _ ", "|_|", " _|"],
+        ':' => [" ", ".", "."],
+        '.' => [" ", " ", "."],
+        '-' => ["   ", " _ ", "   "],
+        _ => [" ", " ", " "],
+    }
+}
+
+/// Displays a time of day or a duration, for example as a stopwatch.
+///
+/// The clock does not update itself. Instead, a new clock with the current
+/// time should be drawn whenever the displayed text would change.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    time: Duration,
+    /// Format string the time is displayed in.
+    ///
+    /// The following placeholders are supported:
+    ///
+    /// - `%H`: Hours, padded to two digits
+    /// - `%M`: Minutes, padded to two digits
+    /// - `%S`: Seconds, padded to two digits
+    /// - `%f`: Milliseconds, padded to three digits
+    /// - `%%`: A literal `%`
+    ///
+    /// All other characters are displayed as-is.
+    pub format: String,
+    pub style: Style,
+    /// Whether the time should be displayed as a large seven-segment display
+    /// three rows high.
+    pub large: bool,
+}
+
+impl Clock {
+    /// A clock displaying a duration.
+    ///
+    /// The hours are not wrapped around after 24 hours.
+    pub fn duration(time: Duration) -> Self {
+        Self {
+            time,
+            format: "%H:%M:%S".to_string(),
+            style: Style::new(),
+            large: false,
+        }
+    }
+
+    /// A clock displaying the time of day at a certain offset to UTC in
+    /// seconds.
+    pub fn time_of_day(time: SystemTime, utc_offset: i32) -> Self {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = (since_epoch.as_secs() as i64 + utc_offset as i64)
+            .rem_euclid(SECONDS_PER_DAY as i64) as u64;
+        let time = Duration::new(secs, since_epoch.subsec_nanos());
+        Self::duration(time)
+    }
+
+    pub fn with_format<S: ToString>(mut self, format: S) -> Self {
+        self.format = format.to_string();
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_large(mut self, large: bool) -> Self {
+        self.large = large;
+        self
+    }
+
+    fn formatted(&self) -> String {
+        let secs = self.time.as_secs();
+        let hours = secs / 60 / 60;
+        let minutes = secs / 60 % 60;
+        let seconds = secs % 60;
+        let millis = self.time.subsec_millis();
+
+        let mut result = String::new();
+        let mut chars = self.format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+
+            // Writing to a String never fails
+            let _ = match chars.next() {
+                Some('H') => write!(result, "{hours:02}"),
+                Some('M') => write!(result, "{minutes:02}"),
+                Some('S') => write!(result, "{seconds:02}"),
+                Some('f') => write!(result, "{millis:03}"),
+                Some('%') => write!(result, "%"),
+                Some(c) => write!(result, "%{c}"),
+                None => write!(result, "%"),
+            };
+        }
+        result
+    }
+
+    fn rows(&self) -> Vec<String> {
+        let text = self.formatted();
+        if !self.large {
+            return vec![text];
+        }
+
+        let mut rows = vec![String::new(), String::new(), String::new()];
+        for (i, c) in text.chars().enumerate() {
+            for (row, segment) in rows.iter_mut().zip(seven_segment(c)) {
+                if i > 0 {
+                    row.push(' ');
+                }
+                row.push_str(segment);
+            }
+        }
+        rows
+    }
+}
+
+impl<E> Widget<E> for Clock {
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        _max_width: Option<u16>,
+        _max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let rows = self.rows();
+        let width = rows.iter().map(|r| widthdb.width(r)).max().unwrap_or(0);
+        let width: u16 = width.try_into().unwrap_or(u16::MAX);
+        let height: u16 = rows.len().try_into().unwrap_or(u16::MAX);
+        Ok(Size::new(width, height))
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        for (y, row) in self.rows().into_iter().enumerate() {
+            frame.write(Pos::new(0, y as i32), (row, self.style));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{Frame, Pos, Size, Styled, Widget, WidthDb};
+
+/// Text flowing through multiple columns of equal width, like in a newspaper.
+///
+/// The text is wrapped to the width of a single column. The resulting lines
+/// are then distributed evenly among the columns from left to right. If they
+/// don't fit into the available height, the remaining lines are cut off.
+#[derive(Debug, Clone)]
+pub struct Columns {
+    pub styled: Styled,
+    columns: u16,
+    pub gap: u16,
+}
+
+impl Columns {
+    pub fn new<S: Into<Styled>>(styled: S, columns: u16) -> Self {
+        assert!(columns > 0);
+        Self {
+            styled: styled.into(),
+            columns,
+            gap: 2,
+        }
+    }
+
+    pub fn columns(&self) -> u16 {
+        self.columns
+    }
+
+    pub fn set_columns(&mut self, columns: u16) {
+        assert!(columns > 0);
+        self.columns = columns;
+    }
+
+    pub fn with_columns(mut self, columns: u16) -> Self {
+        self.set_columns(columns);
+        self
+    }
+
+    pub fn with_gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    fn total_gap(&self) -> u16 {
+        self.gap.saturating_mul(self.columns - 1)
+    }
+
+    fn column_width(&self, width: Option<u16>) -> usize {
+        width
+            .map(|w| (w.saturating_sub(self.total_gap()) / self.columns) as usize)
+            .unwrap_or(usize::MAX)
+    }
+
+    fn wrapped(&self, widthdb: &mut WidthDb, column_width: usize) -> Vec<Styled> {
+        let indices = widthdb.wrap(self.styled.text(), column_width);
+        self.styled.clone().split_at_indices(&indices)
+    }
+
+    /// Amount of lines in each column.
+    fn column_height(&self, lines: usize, max_height: Option<u16>) -> usize {
+        let even = lines.div_ceil(self.columns.into());
+        match max_height {
+            Some(h) => even.min(h.into()),
+            None => even,
+        }
+    }
+}
+
+impl<E> Widget<E> for Columns {
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let lines = self.wrapped(widthdb, self.column_width(max_width));
+
+        let column_width = lines
+            .iter()
+            .map(|l| l.width_trim_end(widthdb))
+            .max()
+            .unwrap_or(0);
+        let column_width: u16 = column_width.try_into().unwrap_or(u16::MAX);
+        let width = column_width
+            .saturating_mul(self.columns)
+            .saturating_add(self.total_gap());
+
+        let height = self.column_height(lines.len(), max_height);
+        let height: u16 = height.try_into().unwrap_or(u16::MAX);
+
+        Ok(Size::new(width, height))
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let size = frame.size();
+        let column_width = self.column_width(Some(size.width));
+        let lines = self.wrapped(frame.widthdb(), column_width);
+        let column_height = self.column_height(lines.len(), Some(size.height)).max(1);
+
+        for (i, line) in lines.into_iter().enumerate() {
+            let column = i / column_height;
+            if column >= self.columns.into() {
+                break;
+            }
+            let x = column * (column_width + self.gap as usize);
+            let y = i % column_height;
+
+            let x: i32 = x.try_into().unwrap_or(i32::MAX);
+            let y: i32 = y.try_into().unwrap_or(i32::MAX);
+            let width: u16 = column_width.try_into().unwrap_or(u16::MAX);
+
+            // Trailing whitespace may be wider than the column
+            frame.push(Pos::new(x, y), Size::new(width, 1));
+            frame.write(Pos::ZERO, line);
+            frame.pop();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+
+use crate::{AsyncWidget, Frame, Pos, Size, Style, Styled, Theme, Widget, WidthDb};
+
+/// A list of completion candidates floating on top of its inner widget.
+///
+/// The list is displayed below the anchor, or above it if there isn't enough
+/// space below. The anchor is a position relative to the top left corner of
+/// the screen, usually [`super::EditorState::last_word_pos`]. If there are no
+/// candidates, only the inner widget is displayed.
+#[derive(Debug, Clone)]
+pub struct CompletionMenu<I> {
+    pub inner: I,
+    pub candidates: Vec<Styled>,
+    pub selected: Option<usize>,
+    pub anchor: Pos,
+    /// Maximum amount of candidates visible at the same time.
+    pub max_height: u16,
+    pub style: Style,
+    pub selected_style: Style,
+}
+
+impl<I> CompletionMenu<I> {
+    pub fn new<S: Into<Styled>>(
+        inner: I,
+        candidates: Vec<S>,
+        selected: Option<usize>,
+        anchor: Pos,
+    ) -> Self {
+        Self {
+            inner,
+            candidates: candidates.into_iter().map(Into::into).collect(),
+            selected,
+            anchor,
+            max_height: 8,
+            style: Theme::default().menu,
+            selected_style: Theme::default().menu_selected,
+        }
+    }
+
+    pub fn with_max_height(mut self, max_height: u16) -> Self {
+        self.max_height = max_height;
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    pub fn with_theme(self, theme: &Theme) -> Self {
+        self.with_style(theme.menu)
+            .with_selected_style(theme.menu_selected)
+    }
+
+    /// Separate the inner widget from the menu.
+    fn split(self) -> (I, CompletionMenu<()>) {
+        let menu = CompletionMenu {
+            inner: (),
+            candidates: self.candidates,
+            selected: self.selected,
+            anchor: self.anchor,
+            max_height: self.max_height,
+            style: self.style,
+            selected_style: self.selected_style,
+        };
+        (self.inner, menu)
+    }
+
+    fn draw_menu(&self, frame: &mut Frame) {
+        if self.candidates.is_empty() {
+            return;
+        }
+
+        let size = frame.size();
+        let anchor = frame.global_to_local(self.anchor);
+
+        let width = self
+            .candidates
+            .iter()
+            .map(|c| c.width(frame.widthdb()))
+            .max()
+            .unwrap_or(0);
+        let width: u16 = width.try_into().unwrap_or(u16::MAX);
+        let height: u16 = self.candidates.len().try_into().unwrap_or(u16::MAX);
+        let height = height.min(self.max_height).max(1);
+
+        let below = anchor.y + 1;
+        let space_below = i32::from(size.height) - below;
+        let y = if space_below >= height.into() || anchor.y < space_below {
+            below
+        } else {
+            anchor.y - i32::from(height)
+        };
+
+        // Scroll so the selected candidate is visible
+        let selected = self.selected.unwrap_or(0);
+        let first = selected.saturating_sub(height as usize - 1);
+
+        let visible = self.candidates.iter().enumerate().skip(first);
+        for (row, (i, candidate)) in visible.take(height.into()).enumerate() {
+            let style = if Some(i) == self.selected {
+                self.selected_style
+            } else {
+                self.style
+            };
+            let pos = Pos::new(anchor.x, y + row as i32);
+            frame.write(pos, (" ".repeat(width.into()), style));
+            frame.write(pos, candidate.clone());
+        }
+    }
+}
+
+impl<E, I> Widget<E> for CompletionMenu<I>
+where
+    I: Widget<E>,
+{
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        self.inner.size(widthdb, max_width, max_height)
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let (inner, menu) = self.split();
+        inner.draw(frame)?;
+        menu.draw_menu(frame);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, I> AsyncWidget<E> for CompletionMenu<I>
+where
+    I: AsyncWidget<E> + Send + Sync,
+{
+    async fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        self.inner.size(widthdb, max_width, max_height).await
+    }
+
+    async fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let (inner, menu) = self.split();
+        inner.draw(frame).await?;
+        menu.draw_menu(frame);
+        Ok(())
+    }
+}
@@ -1,14 +1,36 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::iter;
+use std::ops::Range;
+use std::sync::OnceLock;
 
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use crossterm::style::Stylize;
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::{Frame, Pos, Size, Style, Styled, Widget, WidthDb};
+use crate::widgets::gutter::{Gutter, LineStarts};
+
+use self::storage::Storage;
+use self::wrap_cache::{wrap_line, Row};
+use crate::{Clipboard, Frame, Pos, Size, Style, Styled, Theme, Widget, WidthDb};
+
+mod completion;
+mod external;
+mod history;
+mod storage;
+#[cfg(feature = "vi")]
+mod vi;
+mod wrap_cache;
+
+#[cfg(feature = "vi")]
+pub use vi::*;
 
 /// Like [`WidthDb::wrap`] but includes a final break index if the text ends
 /// with a newline.
-fn wrap(widthdb: &mut WidthDb, text: &str, width: usize) -> Vec<usize> {
-    let mut breaks = widthdb.wrap(text, width);
+///
+/// Rows continuing a soft-wrapped line have `indent` fewer columns.
+fn wrap(widthdb: &mut WidthDb, text: &str, width: usize, indent: usize) -> Vec<usize> {
+    let mut breaks = widthdb.wrap_indented(text, width, 0, indent);
     if text.ends_with('\n') {
         breaks.push(text.len())
     }
@@ -19,9 +41,59 @@ fn wrap(widthdb: &mut WidthDb, text: &str, width: usize) -> Vec<usize> {
 // State //
 ///////////
 
+/// The result of handling an event in an [`EditorState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditorAction {
+    /// The event was not handled by the editor.
+    Ignored,
+    /// The event was handled but didn't modify the text or move the cursor.
+    Handled,
+    /// The cursor was moved or the selection changed.
+    CursorMoved,
+    /// The text was (potentially) modified.
+    TextChanged,
+    /// The user wants to submit the text.
+    Submit,
+}
+
+/// How indentation is written when it is copied to a new line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Indentation {
+    /// Copy the indentation unchanged.
+    Keep,
+    /// Use as many tabs as possible, followed by spaces.
+    Tabs,
+    /// Use only spaces.
+    Spaces,
+}
+
+/// What pressing the tab key inserts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TabBehavior {
+    /// A literal tab character.
+    Tab,
+    /// A fixed amount of spaces.
+    Spaces(u8),
+    /// Enough spaces to reach the next tab stop.
+    ToTabStop,
+}
+
+/// Maximum amount of entries in the kill ring of an [`EditorState`].
+const KILL_RING_SIZE: usize = 32;
+
 #[derive(Debug, Clone)]
-pub struct EditorState {
+struct Yank {
+    /// Range of the yanked text.
+    range: Range<usize>,
+    /// Index of the yanked kill ring entry.
+    index: usize,
+    /// The yanked text after filtering.
     text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct EditorState {
+    text: Storage,
 
     /// Index of the cursor in the text.
     ///
@@ -29,11 +101,60 @@ pub struct EditorState {
     cursor_idx: usize,
 
     /// Column of the cursor on the screen just after it was last moved
-    /// horizontally.
-    cursor_col: usize,
+    /// horizontally, or `None` if it needs to be recomputed.
+    cursor_col: Option<usize>,
+
+    /// Index of the other end of the selection in the text, if any.
+    ///
+    /// Must point to a valid grapheme boundary. The selection is empty if it
+    /// is equal to the cursor index.
+    selection_anchor: Option<usize>,
+
+    /// Previously killed text, most recent last.
+    kill_ring: Vec<String>,
+
+    /// The text inserted by the most recent yank, if any.
+    last_yank: Option<Yank>,
+
+    /// State of the vi mode, if enabled.
+    #[cfg(feature = "vi")]
+    vi: Option<vi::ViState>,
+
+    /// Only characters for which this returns `true` may be inserted.
+    filter: Option<fn(&str, char) -> bool>,
+
+    /// Maximum amount of graphemes the user may enter.
+    max_graphemes: Option<usize>,
+
+    /// Whether and how new lines should be indented like the previous line.
+    auto_indent: Option<Indentation>,
+
+    tab_behavior: TabBehavior,
+
+    /// Whether the user is prevented from modifying the text.
+    read_only: bool,
+
+    /// Revision of the text when it was last marked as clean.
+    clean_revision: u64,
+
+    /// Previously submitted texts, if enabled.
+    history: Option<history::History>,
+
+    /// The pattern to search for, if any. Never empty.
+    search: Option<String>,
+
+    /// Index of the first visible row when the editor was last rendered.
+    scroll_offset: usize,
+
+    /// Candidates for completing the word under the cursor, if any.
+    completion: Option<completion::Completion>,
 
     /// Position of the cursor when the editor was last rendered.
     last_cursor_pos: Pos,
+
+    /// Screen position of the start of the word under the cursor when the
+    /// editor was last rendered.
+    last_word_pos: Pos,
 }
 
 impl EditorState {
@@ -44,9 +165,25 @@ impl EditorState {
     pub fn with_initial_text(text: String) -> Self {
         Self {
             cursor_idx: text.len(),
-            cursor_col: 0,
+            cursor_col: Some(0),
+            selection_anchor: None,
+            kill_ring: vec![],
+            last_yank: None,
+            #[cfg(feature = "vi")]
+            vi: None,
+            filter: None,
+            max_graphemes: None,
+            auto_indent: None,
+            tab_behavior: TabBehavior::Tab,
+            read_only: false,
+            clean_revision: 0,
+            history: None,
+            search: None,
+            scroll_offset: 0,
+            completion: None,
             last_cursor_pos: Pos::ZERO,
-            text,
+            last_word_pos: Pos::ZERO,
+            text: Storage::new(text),
         }
     }
 
@@ -54,11 +191,79 @@ impl EditorState {
     // Grapheme helper functions //
     ///////////////////////////////
 
-    fn grapheme_boundaries(&self) -> Vec<usize> {
-        self.text
-            .grapheme_indices(true)
-            .map(|(i, _)| i)
-            .chain(iter::once(self.text.len()))
+    /// The graphemes of a line along with their ranges in the text and
+    /// whether they consist only of whitespace.
+    ///
+    /// Since lines end in a newline, graphemes never span multiple lines.
+    fn line_graphemes(&self, line: usize) -> Vec<(Range<usize>, bool)> {
+        let range = self.text.line_range(line);
+        let text = self.text.slice(range.clone());
+        text.grapheme_indices(true)
+            .map(|(i, g)| {
+                let whitespace = g.chars().all(|c| c.is_whitespace());
+                (range.start + i..range.start + i + g.len(), whitespace)
+            })
+            .collect()
+    }
+
+    /// Like [`Self::line_graphemes`], but for all graphemes ending at or
+    /// before an index, nearest first.
+    ///
+    /// Lines are only looked at once the iterator reaches them.
+    fn graphemes_before(&self, idx: usize) -> impl Iterator<Item = (Range<usize>, bool)> + '_ {
+        let (line, _) = self.text.line_at(idx);
+        (0..=line)
+            .rev()
+            .flat_map(|line| self.line_graphemes(line).into_iter().rev())
+            .filter(move |(range, _)| range.end <= idx)
+    }
+
+    /// Like [`Self::line_graphemes`], but for all graphemes starting at or
+    /// after an index, nearest first.
+    ///
+    /// Lines are only looked at once the iterator reaches them.
+    fn graphemes_after(&self, idx: usize) -> impl Iterator<Item = (Range<usize>, bool)> + '_ {
+        let (line, _) = self.text.line_at(idx);
+        (line..self.text.len_lines())
+            .flat_map(|line| self.line_graphemes(line))
+            .filter(move |(range, _)| range.start >= idx)
+    }
+
+    /// The word containing an index, where a word is a sequence of
+    /// non-whitespace graphemes.
+    ///
+    /// Returns an empty range at the index if it is surrounded by whitespace.
+    fn word_at(&self, idx: usize) -> Range<usize> {
+        let start = self
+            .graphemes_before(idx)
+            .take_while(|(_, whitespace)| !whitespace)
+            .last()
+            .map(|(range, _)| range.start)
+            .unwrap_or(idx);
+        let end = self
+            .graphemes_after(idx)
+            .take_while(|(_, whitespace)| !whitespace)
+            .last()
+            .map(|(range, _)| range.end)
+            .unwrap_or(idx);
+        start..end
+    }
+
+    /// The grapheme boundaries in the line containing the index and the line
+    /// before it.
+    ///
+    /// This is enough to find the graphemes directly before and after the
+    /// index without looking at the entire text.
+    fn grapheme_boundaries_near(&self, idx: usize) -> Vec<usize> {
+        let (line, range) = self.text.line_at(idx);
+        let start = match line {
+            0 => range.start,
+            line => self.text.line_range(line - 1).start,
+        };
+        let text = self.text.slice(start..range.end);
+        text.grapheme_indices(true)
+            .map(|(i, _)| start + i)
+            .chain(iter::once(range.end))
             .collect()
     }
 
@@ -67,7 +272,8 @@ impl EditorState {
     ///
     /// Can handle arbitrary cursor index.
     fn move_cursor_to_grapheme_boundary(&mut self) {
-        for i in self.grapheme_boundaries() {
+        let idx = self.text.floor_char_boundary(self.cursor_idx);
+        for i in self.grapheme_boundaries_near(idx) {
             #[allow(clippy::comparison_chain)]
             if i == self.cursor_idx {
                 // We're at a valid grapheme boundary already
@@ -88,54 +294,37 @@ impl EditorState {
     // Line/col helper functions //
     ///////////////////////////////
 
-    /// Like [`Self::grapheme_boundaries`] but for lines.
-    ///
-    /// Note that the last line can have a length of 0 if the text ends with a
-    /// newline.
-    fn line_boundaries(&self) -> Vec<usize> {
-        let newlines = self
-            .text
-            .char_indices()
-            .filter(|(_, c)| *c == '\n')
-            .map(|(i, _)| i + 1); // utf-8 encodes '\n' as a single byte
-        iter::once(0)
-            .chain(newlines)
-            .chain(iter::once(self.text.len()))
-            .collect()
-    }
-
     /// Find the cursor's current line.
     ///
     /// Returns `(line_nr, start_idx, end_idx)`.
-    fn cursor_line(&self, boundaries: &[usize]) -> (usize, usize, usize) {
-        let mut result = (0, 0, 0);
-        for (i, (start, end)) in boundaries.iter().zip(boundaries.iter().skip(1)).enumerate() {
-            if self.cursor_idx >= *start {
-                result = (i, *start, *end);
-            } else {
-                break;
-            }
+    fn cursor_line(&self) -> (usize, usize, usize) {
+        let (line, range) = self.text.line_at(self.cursor_idx);
+        (line, range.start, range.end)
+    }
+
+    /// Find the start and end of the cursor's current line, excluding the
+    /// trailing newline.
+    fn cursor_line_range(&self) -> (usize, usize) {
+        let (_, start, end) = self.cursor_line();
+        if self.text.slice(start..end).ends_with('\n') {
+            (start, end - 1)
+        } else {
+            (start, end)
         }
-        result
     }
 
     fn cursor_col(&self, widthdb: &mut WidthDb, line_start: usize) -> usize {
-        widthdb.width(&self.text[line_start..self.cursor_idx])
+        widthdb.width(&self.text.slice(line_start..self.cursor_idx))
     }
 
     fn line(&self, line: usize) -> (usize, usize) {
-        let boundaries = self.line_boundaries();
-        boundaries
-            .iter()
-            .copied()
-            .zip(boundaries.iter().copied().skip(1))
-            .nth(line)
-            .expect("line exists")
+        let range = self.text.line_range(line);
+        (range.start, range.end)
     }
 
     fn move_cursor_to_line_col(&mut self, widthdb: &mut WidthDb, line: usize, col: usize) {
         let (start, end) = self.line(line);
-        let line = &self.text[start..end];
+        let line = self.text.slice(start..end);
 
         let mut width = 0;
         for (gi, g) in line.grapheme_indices(true) {
@@ -152,54 +341,383 @@ impl EditorState {
         }
     }
 
+    /// Find the start of the word before the cursor, skipping any whitespace
+    /// between the word and the cursor.
+    fn word_start_before_cursor(&self) -> usize {
+        let mut result = self.cursor_idx;
+        let mut encountered_word = false;
+        for (range, whitespace) in self.graphemes_before(self.cursor_idx) {
+            if encountered_word && whitespace {
+                break;
+            } else if !whitespace {
+                encountered_word = true;
+            }
+            result = range.start;
+        }
+        result
+    }
+
+    /// Find the end of the word after the cursor, skipping any whitespace
+    /// between the cursor and the word.
+    fn word_end_after_cursor(&self) -> usize {
+        let mut result = self.cursor_idx;
+        let mut encountered_word = false;
+        for (range, whitespace) in self.graphemes_after(self.cursor_idx) {
+            if encountered_word && whitespace {
+                break;
+            } else if !whitespace {
+                encountered_word = true;
+            }
+            result = range.end;
+        }
+        result
+    }
+
     fn record_cursor_col(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.line_boundaries();
-        let (_, start, _) = self.cursor_line(&boundaries);
-        self.cursor_col = self.cursor_col(widthdb, start);
+        let (_, start, _) = self.cursor_line();
+        self.cursor_col = Some(self.cursor_col(widthdb, start));
+    }
+
+    /// The column recorded by [`Self::record_cursor_col`], recomputing it if
+    /// necessary.
+    fn recorded_cursor_col(&mut self, widthdb: &mut WidthDb) -> usize {
+        match self.cursor_col {
+            Some(col) => col,
+            None => {
+                let (_, start, _) = self.cursor_line();
+                let col = self.cursor_col(widthdb, start);
+                self.cursor_col = Some(col);
+                col
+            }
+        }
     }
 
     /////////////
     // Editing //
     /////////////
 
+    /// The current text.
+    ///
+    /// With the `ropey` feature, this builds a contiguous string the first time
+    /// it is called after the text was modified.
     pub fn text(&self) -> &str {
-        &self.text
+        self.text.as_str()
     }
 
     pub fn set_text(&mut self, widthdb: &mut WidthDb, text: String) {
-        self.text = text;
+        if self.read_only {
+            return;
+        }
+
+        self.text.set(text);
+        self.selection_anchor = None;
         self.move_cursor_to_grapheme_boundary();
         self.record_cursor_col(widthdb);
     }
 
     pub fn clear(&mut self) {
-        self.text = String::new();
+        if self.read_only {
+            return;
+        }
+
+        self.text.set(String::new());
         self.cursor_idx = 0;
-        self.cursor_col = 0;
+        self.cursor_col = Some(0);
+        self.selection_anchor = None;
+    }
+
+    /// A number that increases whenever the text is modified.
+    ///
+    /// Can be used to cheaply detect whether the text changed, for example to
+    /// invalidate caches. Changes to the cursor or selection don't affect the
+    /// revision.
+    pub fn revision(&self) -> u64 {
+        self.text.revision()
+    }
+
+    /// Whether the text was modified since it was last marked as clean.
+    ///
+    /// Initially, the text is clean.
+    pub fn is_dirty(&self) -> bool {
+        self.text.revision() != self.clean_revision
+    }
+
+    /// Mark the current text as clean, for example after saving it.
+    pub fn mark_clean(&mut self) {
+        self.clean_revision = self.text.revision();
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Prevent the user from modifying the text.
+    ///
+    /// While read-only, all functions that would modify the text do nothing.
+    /// The cursor can still be moved, and text can still be selected and
+    /// copied. This allows using the editor as a navigable text viewer. To
+    /// replace the viewed text, disable read-only mode temporarily or create a
+    /// new state via [`Self::with_initial_text`].
+    pub fn set_read_only(&mut self, active: bool) {
+        self.read_only = active;
+    }
+
+    pub fn with_read_only(mut self, active: bool) -> Self {
+        self.set_read_only(active);
+        self
     }
 
     /// Insert a character at the current cursor position and move the cursor
     /// accordingly.
+    ///
+    /// Replaces the selection if there is one.
     pub fn insert_char(&mut self, widthdb: &mut WidthDb, ch: char) {
-        self.text.insert(self.cursor_idx, ch);
-        self.cursor_idx += ch.len_utf8();
-        self.record_cursor_col(widthdb);
+        self.insert_str(widthdb, ch.encode_utf8(&mut [0; 4]));
     }
 
     /// Insert a string at the current cursor position and move the cursor
     /// accordingly.
+    ///
+    /// Replaces the selection if there is one. Characters rejected by the
+    /// filter and graphemes exceeding the maximum amount of graphemes are
+    /// dropped. The selection is deleted even if nothing remains.
     pub fn insert_str(&mut self, widthdb: &mut WidthDb, str: &str) {
-        self.text.insert_str(self.cursor_idx, str);
+        if self.read_only {
+            return;
+        }
+
+        let selection = self.selection().unwrap_or(self.cursor_idx..self.cursor_idx);
+        let str = self.filter_input(str, selection);
+        self.delete_selection(widthdb);
+        if str.is_empty() {
+            return;
+        }
+
+        self.text.insert_str(self.cursor_idx, &str);
         self.cursor_idx += str.len();
         self.record_cursor_col(widthdb);
     }
 
+    /// Copy the indentation of the current line to new lines inserted via
+    /// [`Self::insert_newline`], or disable this by passing `None`.
+    pub fn set_auto_indent(&mut self, indentation: Option<Indentation>) {
+        self.auto_indent = indentation;
+    }
+
+    pub fn with_auto_indent(mut self, indentation: Indentation) -> Self {
+        self.set_auto_indent(Some(indentation));
+        self
+    }
+
+    /// Insert a newline at the current cursor position, followed by the
+    /// indentation of the current line if auto indent is enabled.
+    ///
+    /// Replaces the selection if there is one.
+    pub fn insert_newline(&mut self, widthdb: &mut WidthDb) {
+        let Some(indentation) = self.auto_indent else {
+            self.insert_char(widthdb, '\n');
+            return;
+        };
+
+        let (_, start, _) = self.cursor_line();
+        let line = self.text.slice(start..self.cursor_idx);
+        let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+
+        let width = widthdb.width(indent);
+        let tab_width = widthdb.tab_width as usize;
+        let indent = match indentation {
+            Indentation::Keep => indent.to_string(),
+            Indentation::Tabs => "\t".repeat(width / tab_width) + &" ".repeat(width % tab_width),
+            Indentation::Spaces => " ".repeat(width),
+        };
+
+        self.insert_str(widthdb, &format!("\n{indent}"));
+    }
+
+    pub fn set_tab_behavior(&mut self, behavior: TabBehavior) {
+        self.tab_behavior = behavior;
+    }
+
+    pub fn with_tab_behavior(mut self, behavior: TabBehavior) -> Self {
+        self.set_tab_behavior(behavior);
+        self
+    }
+
+    /// Insert what the tab key should insert according to the current
+    /// [`TabBehavior`].
+    ///
+    /// Replaces the selection if there is one.
+    pub fn insert_tab(&mut self, widthdb: &mut WidthDb) {
+        let tab_width = widthdb.tab_width as usize;
+        match self.tab_behavior {
+            TabBehavior::Tab => self.insert_char(widthdb, '\t'),
+            TabBehavior::Spaces(n) => self.insert_str(widthdb, &" ".repeat(n.into())),
+            TabBehavior::ToTabStop => {
+                self.delete_selection(widthdb);
+                let (_, start, _) = self.cursor_line();
+                let col = self.cursor_col(widthdb, start);
+                let spaces = tab_width - col % tab_width;
+                self.insert_str(widthdb, &" ".repeat(spaces));
+            }
+        }
+    }
+
+    /// Remove one level of indentation from the start of the current line, or
+    /// from all lines touched by the selection.
+    ///
+    /// One level is either a single tab or up to as many spaces as the tab key
+    /// would insert.
+    pub fn dedent(&mut self, widthdb: &mut WidthDb) {
+        if self.read_only {
+            return;
+        }
+
+        let level = match self.tab_behavior {
+            TabBehavior::Spaces(n) => n.into(),
+            TabBehavior::Tab | TabBehavior::ToTabStop => widthdb.tab_width as usize,
+        };
+
+        let (first, last) = match self.selection() {
+            Some(selection) => {
+                let (first, _) = self.text.line_at(selection.start);
+                let (last, range) = self.text.line_at(selection.end);
+                // A selection ending at the start of a line doesn't touch it
+                if range.start == selection.end && last > first {
+                    (first, last - 1)
+                } else {
+                    (first, last)
+                }
+            }
+            None => {
+                let (line, _, _) = self.cursor_line();
+                (line, line)
+            }
+        };
+
+        for line in (first..=last).rev() {
+            let range = self.text.line_range(line);
+            let start = range.start;
+            let rest = self.text.slice(range);
+            let len = if rest.starts_with('\t') {
+                1
+            } else {
+                rest.chars().take(level).take_while(|c| *c == ' ').count()
+            };
+            if len == 0 {
+                continue;
+            }
+
+            // Indices inside the removed indentation move to its start
+            let shift = |idx: usize| idx - len.min(idx.saturating_sub(start));
+            self.selection_anchor = self.selection_anchor.map(shift);
+            self.cursor_idx = shift(self.cursor_idx);
+            self.text.replace_range(start..start + len, "");
+        }
+
+        self.record_cursor_col(widthdb);
+    }
+
+    /// Insert pasted text at the cursor position, replacing the selection if
+    /// there is one.
+    ///
+    /// Unlike [`Self::insert_str`], this normalizes `\r\n` and `\r` to `\n`
+    /// and removes all other control characters except for tabs. This makes it
+    /// suitable for inserting bracketed paste payloads.
+    pub fn paste(&mut self, widthdb: &mut WidthDb, text: &str) {
+        let mut normalized = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\r' => {
+                    chars.next_if_eq(&'\n');
+                    normalized.push('\n');
+                }
+                '\n' | '\t' => normalized.push(ch),
+                ch if ch.is_control() => {}
+                ch => normalized.push(ch),
+            }
+        }
+
+        self.insert_str(widthdb, &normalized);
+    }
+
+    ///////////////
+    // Filtering //
+    ///////////////
+
+    /// Only allow inserting characters for which the filter returns `true`.
+    ///
+    /// The filter receives the current text and the character to be inserted.
+    /// It is only applied to text inserted by the user, not to text set via
+    /// [`Self::set_text`]. With the `ropey` feature, passing the current text
+    /// requires building it as a contiguous string, like [`Self::text`].
+    pub fn set_filter(&mut self, filter: Option<fn(&str, char) -> bool>) {
+        self.filter = filter;
+    }
+
+    pub fn with_filter(mut self, filter: fn(&str, char) -> bool) -> Self {
+        self.set_filter(Some(filter));
+        self
+    }
+
+    /// Limit the amount of graphemes the user can enter.
+    ///
+    /// Like the filter, this limit is not applied to text set via
+    /// [`Self::set_text`].
+    pub fn set_max_graphemes(&mut self, max_graphemes: Option<usize>) {
+        self.max_graphemes = max_graphemes;
+    }
+
+    pub fn with_max_graphemes(mut self, max_graphemes: usize) -> Self {
+        self.set_max_graphemes(Some(max_graphemes));
+        self
+    }
+
+    /// Apply the filter and grapheme limit to text that is about to replace a
+    /// range of the current text.
+    fn filter_input(&self, str: &str, replaced: Range<usize>) -> String {
+        let mut result = match self.filter {
+            Some(filter) => {
+                let text = self.text.as_str();
+                str.chars().filter(|c| filter(text, *c)).collect()
+            }
+            None => str.to_string(),
+        };
+
+        if let Some(max_graphemes) = self.max_graphemes {
+            let (_, first) = self.text.line_at(replaced.start);
+            let (_, last) = self.text.line_at(replaced.end);
+            let prefix = self.text.slice(first.start..replaced.start);
+            let suffix = self.text.slice(replaced.end..last.end);
+            let current = self.text.graphemes()
+                - self
+                    .text
+                    .slice(first.start..last.end)
+                    .graphemes(true)
+                    .count()
+                + prefix.graphemes(true).count()
+                + suffix.graphemes(true).count();
+            let remaining = max_graphemes.saturating_sub(current);
+            if let Some((i, _)) = result.grapheme_indices(true).nth(remaining) {
+                result.truncate(i);
+            }
+        }
+
+        result
+    }
+
     /// Delete the grapheme before the cursor position.
+    ///
+    /// Deletes only the selection if there is one.
     pub fn backspace(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.grapheme_boundaries();
+        if self.read_only || self.delete_selection(widthdb) {
+            return;
+        }
+
+        let boundaries = self.grapheme_boundaries_near(self.cursor_idx);
         for (start, end) in boundaries.iter().zip(boundaries.iter().skip(1)) {
             if *end == self.cursor_idx {
-                self.text.replace_range(start..end, "");
+                self.text.replace_range(*start..*end, "");
                 self.cursor_idx = *start;
                 self.record_cursor_col(widthdb);
                 break;
@@ -208,22 +726,207 @@ impl EditorState {
     }
 
     /// Delete the grapheme after the cursor position.
+    ///
+    /// Deletes only the selection if there is one.
     pub fn delete(&mut self) {
-        let boundaries = self.grapheme_boundaries();
+        if self.read_only {
+            return;
+        }
+        if let Some(selection) = self.selection() {
+            self.remove_range(selection);
+            return;
+        }
+        self.selection_anchor = None;
+
+        let boundaries = self.grapheme_boundaries_near(self.cursor_idx);
         for (start, end) in boundaries.iter().zip(boundaries.iter().skip(1)) {
             if *start == self.cursor_idx {
-                self.text.replace_range(start..end, "");
+                self.text.replace_range(*start..*end, "");
                 break;
             }
         }
     }
 
+    /// Delete a range of the text, move the cursor to its start, and clear
+    /// the selection.
+    fn delete_range(&mut self, widthdb: &mut WidthDb, range: Range<usize>) {
+        if self.read_only {
+            return;
+        }
+
+        self.remove_range(range);
+        self.record_cursor_col(widthdb);
+    }
+
+    /// Like [`Self::delete_range`], but the cursor column is only recomputed
+    /// once it is needed.
+    fn remove_range(&mut self, range: Range<usize>) {
+        self.selection_anchor = None;
+        self.cursor_idx = range.start;
+        self.cursor_col = None;
+        self.text.replace_range(range, "");
+    }
+
+    /// Delete the text from the start of the previous word to the cursor.
+    ///
+    /// Deletes only the selection if there is one.
+    pub fn delete_word_before_cursor(&mut self, widthdb: &mut WidthDb) {
+        if self.delete_selection(widthdb) {
+            return;
+        }
+        self.delete_range(widthdb, self.word_start_before_cursor()..self.cursor_idx);
+    }
+
+    /// Delete the text from the cursor to the end of the next word.
+    ///
+    /// Deletes only the selection if there is one.
+    pub fn delete_word_after_cursor(&mut self, widthdb: &mut WidthDb) {
+        if self.delete_selection(widthdb) {
+            return;
+        }
+        self.delete_range(widthdb, self.cursor_idx..self.word_end_after_cursor());
+    }
+
+    /// Delete the text from the start of the cursor's line to the cursor and
+    /// add it to the kill ring.
+    pub fn delete_to_start_of_line(&mut self, widthdb: &mut WidthDb) {
+        let (start, _) = self.cursor_line_range();
+        self.kill(widthdb, start..self.cursor_idx);
+    }
+
+    /// Delete the text from the cursor to the end of its line and add it to
+    /// the kill ring.
+    ///
+    /// Unlike [`Self::kill_to_end_of_line`], this never deletes the newline.
+    pub fn delete_to_end_of_line(&mut self, widthdb: &mut WidthDb) {
+        let (_, end) = self.cursor_line_range();
+        self.kill(widthdb, self.cursor_idx..end);
+    }
+
+    /// Swap the grapheme before the cursor with the grapheme after it and
+    /// move the cursor forward.
+    ///
+    /// At the end of a line, swap the two graphemes before the cursor instead.
+    /// Never swaps graphemes across line boundaries.
+    pub fn transpose_graphemes(&mut self, widthdb: &mut WidthDb) {
+        if self.read_only {
+            return;
+        }
+
+        let boundaries = self.grapheme_boundaries_near(self.cursor_idx);
+        let Some(i) = boundaries.iter().position(|b| *b == self.cursor_idx) else {
+            return;
+        };
+
+        let (_, line_end) = self.cursor_line_range();
+        let at_line_end = self.cursor_idx == line_end;
+        let i = if at_line_end { i.saturating_sub(1) } else { i };
+        if i == 0 || i + 1 >= boundaries.len() {
+            return;
+        }
+
+        let first = boundaries[i - 1]..boundaries[i];
+        let second = boundaries[i]..boundaries[i + 1];
+        let first_text = self.text.slice(first.clone());
+        let second_text = self.text.slice(second.clone());
+        if first_text.contains('\n') || second_text.contains('\n') {
+            return;
+        }
+
+        let swapped = format!("{second_text}{first_text}");
+        self.text.replace_range(first.start..second.end, &swapped);
+        self.selection_anchor = None;
+        self.cursor_idx = second.end;
+        self.move_cursor_to_grapheme_boundary();
+        self.record_cursor_col(widthdb);
+    }
+
+    /// Swap the word before the cursor with the word after it and move the
+    /// cursor to the end of the latter.
+    ///
+    /// If the cursor is inside a word, that word counts as the word after the
+    /// cursor. At the end of the text, swap the last two words.
+    pub fn transpose_words(&mut self, widthdb: &mut WidthDb) {
+        if self.read_only {
+            return;
+        }
+
+        let second = self
+            .graphemes_after(self.cursor_idx)
+            .find(|(_, whitespace)| !whitespace)
+            .or_else(|| {
+                self.graphemes_before(self.text.len())
+                    .find(|(_, whitespace)| !whitespace)
+            });
+        let Some((second, _)) = second else {
+            return;
+        };
+        let second = self.word_at(second.start);
+
+        let first = self
+            .graphemes_before(second.start)
+            .find(|(_, whitespace)| !whitespace);
+        let Some((first, _)) = first else {
+            return;
+        };
+        let first = self.word_at(first.start);
+
+        let swapped = format!(
+            "{}{}{}",
+            self.text.slice(second.clone()),
+            self.text.slice(first.end..second.start),
+            self.text.slice(first.clone()),
+        );
+        self.text.replace_range(first.start..second.end, &swapped);
+        self.selection_anchor = None;
+        self.cursor_idx = second.end;
+        self.record_cursor_col(widthdb);
+    }
+
     /////////////////////
     // Cursor movement //
     /////////////////////
 
+    /// Index of the cursor in the text, in bytes.
+    pub fn cursor_byte_idx(&self) -> usize {
+        self.cursor_idx
+    }
+
+    /// Move the cursor to an index in the text, in bytes.
+    ///
+    /// If the index doesn't lie on a grapheme boundary, the cursor is moved to
+    /// the next grapheme boundary. If the index is out of bounds, the cursor
+    /// is moved to the end of the text.
+    pub fn set_cursor_byte_idx(&mut self, widthdb: &mut WidthDb, idx: usize) {
+        self.selection_anchor = None;
+        self.cursor_idx = idx;
+        self.move_cursor_to_grapheme_boundary();
+        self.record_cursor_col(widthdb);
+    }
+
+    /// The cursor's line and column, both starting at 0.
+    ///
+    /// The column is measured in terminal columns, not graphemes or bytes.
+    pub fn cursor_line_col(&self, widthdb: &mut WidthDb) -> (usize, usize) {
+        let (line, start, _) = self.cursor_line();
+        (line, self.cursor_col(widthdb, start))
+    }
+
+    /// Move the cursor to a line and column, both starting at 0.
+    ///
+    /// The column is measured in terminal columns. If the line is out of
+    /// bounds, the cursor is moved to the last line. If the column is out of
+    /// bounds, the cursor is moved to the end of the line.
+    pub fn set_cursor_line_col(&mut self, widthdb: &mut WidthDb, line: usize, col: usize) {
+        self.selection_anchor = None;
+        let line = line.min(self.text.len_lines() - 1);
+        self.move_cursor_to_line_col(widthdb, line, col);
+        self.record_cursor_col(widthdb);
+    }
+
     pub fn move_cursor_left(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.grapheme_boundaries();
+        self.selection_anchor = None;
+        let boundaries = self.grapheme_boundaries_near(self.cursor_idx);
         for (start, end) in boundaries.iter().zip(boundaries.iter().skip(1)) {
             if *end == self.cursor_idx {
                 self.cursor_idx = *start;
@@ -234,7 +937,8 @@ impl EditorState {
     }
 
     pub fn move_cursor_right(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.grapheme_boundaries();
+        self.selection_anchor = None;
+        let boundaries = self.grapheme_boundaries_near(self.cursor_idx);
         for (start, end) in boundaries.iter().zip(boundaries.iter().skip(1)) {
             if *start == self.cursor_idx {
                 self.cursor_idx = *end;
@@ -245,85 +949,643 @@ impl EditorState {
     }
 
     pub fn move_cursor_left_a_word(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.grapheme_boundaries();
-        let mut encountered_word = false;
-        for (start, end) in boundaries.iter().zip(boundaries.iter().skip(1)).rev() {
-            if *end == self.cursor_idx {
-                let g = &self.text[*start..*end];
-                let whitespace = g.chars().all(|c| c.is_whitespace());
-                if encountered_word && whitespace {
-                    break;
-                } else if !whitespace {
-                    encountered_word = true;
-                }
-                self.cursor_idx = *start;
-            }
-        }
+        self.selection_anchor = None;
+        self.cursor_idx = self.word_start_before_cursor();
         self.record_cursor_col(widthdb);
     }
 
     pub fn move_cursor_right_a_word(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.grapheme_boundaries();
-        let mut encountered_word = false;
-        for (start, end) in boundaries.iter().zip(boundaries.iter().skip(1)) {
-            if *start == self.cursor_idx {
-                let g = &self.text[*start..*end];
-                let whitespace = g.chars().all(|c| c.is_whitespace());
-                if encountered_word && whitespace {
-                    break;
-                } else if !whitespace {
-                    encountered_word = true;
-                }
-                self.cursor_idx = *end;
-            }
-        }
+        self.selection_anchor = None;
+        self.cursor_idx = self.word_end_after_cursor();
         self.record_cursor_col(widthdb);
     }
 
     pub fn move_cursor_to_start_of_line(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.line_boundaries();
-        let (line, _, _) = self.cursor_line(&boundaries);
+        self.selection_anchor = None;
+        let (line, _, _) = self.cursor_line();
         self.move_cursor_to_line_col(widthdb, line, 0);
         self.record_cursor_col(widthdb);
     }
 
     pub fn move_cursor_to_end_of_line(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.line_boundaries();
-        let (line, _, _) = self.cursor_line(&boundaries);
+        self.selection_anchor = None;
+        let (line, _, _) = self.cursor_line();
         self.move_cursor_to_line_col(widthdb, line, usize::MAX);
         self.record_cursor_col(widthdb);
     }
 
     pub fn move_cursor_up(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.line_boundaries();
-        let (line, _, _) = self.cursor_line(&boundaries);
+        self.selection_anchor = None;
+        let (line, _, _) = self.cursor_line();
         if line > 0 {
-            self.move_cursor_to_line_col(widthdb, line - 1, self.cursor_col);
+            let col = self.recorded_cursor_col(widthdb);
+            self.move_cursor_to_line_col(widthdb, line - 1, col);
         }
     }
 
     pub fn move_cursor_down(&mut self, widthdb: &mut WidthDb) {
-        let boundaries = self.line_boundaries();
+        self.selection_anchor = None;
+        let (line, _, _) = self.cursor_line();
+        if line + 1 < self.text.len_lines() {
+            let col = self.recorded_cursor_col(widthdb);
+            self.move_cursor_to_line_col(widthdb, line + 1, col);
+        }
+    }
 
-        // There's always at least one line, and always at least two line
-        // boundaries at 0 and self.text.len().
-        let amount_of_lines = boundaries.len() - 1;
+    ///////////////
+    // Selection //
+    ///////////////
 
-        let (line, _, _) = self.cursor_line(&boundaries);
-        if line + 1 < amount_of_lines {
-            self.move_cursor_to_line_col(widthdb, line + 1, self.cursor_col);
+    /// The selected range of the text, if any.
+    ///
+    /// Returns `None` if the selection is empty.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.selection_anchor?;
+        match anchor.cmp(&self.cursor_idx) {
+            Ordering::Less => Some(anchor..self.cursor_idx),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(self.cursor_idx..anchor),
         }
     }
 
-    pub fn last_cursor_pos(&self) -> Pos {
+    pub fn selected_text(&self) -> Option<Cow<'_, str>> {
+        self.selection().map(|r| self.text.slice(r))
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection_anchor = None;
+    }
+
+    pub fn select_all(&mut self, widthdb: &mut WidthDb) {
+        self.selection_anchor = Some(0);
+        self.cursor_idx = self.text.len();
+        self.record_cursor_col(widthdb);
+    }
+
+    /// Delete the selected text and move the cursor to where it was.
+    ///
+    /// Returns `true` if any text was deleted.
+    pub fn delete_selection(&mut self, widthdb: &mut WidthDb) -> bool {
+        if self.read_only {
+            return false;
+        }
+
+        let Some(selection) = self.selection() else {
+            self.selection_anchor = None;
+            return false;
+        };
+
+        self.delete_range(widthdb, selection);
+        true
+    }
+
+    /// Replace the selected text with a string and move the cursor to its
+    /// end.
+    ///
+    /// If there is no selection, the string is inserted at the current cursor
+    /// position.
+    pub fn replace_selection(&mut self, widthdb: &mut WidthDb, str: &str) {
+        self.insert_str(widthdb, str);
+    }
+
+    /// Copy the selected text to the clipboard.
+    ///
+    /// Returns `true` if any text was copied.
+    pub fn copy(&self, clipboard: &mut dyn Clipboard) -> bool {
+        match self.selected_text() {
+            Some(text) => {
+                clipboard.set(text.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Copy the selected text to the clipboard and delete it.
+    ///
+    /// Returns `true` if any text was cut.
+    pub fn cut(&mut self, widthdb: &mut WidthDb, clipboard: &mut dyn Clipboard) -> bool {
+        !self.read_only && self.copy(clipboard) && self.delete_selection(widthdb)
+    }
+
+    /// Insert the clipboard contents at the cursor position like
+    /// [`Self::paste`].
+    ///
+    /// Returns `true` if the clipboard was not empty.
+    pub fn paste_from_clipboard(
+        &mut self,
+        widthdb: &mut WidthDb,
+        clipboard: &mut dyn Clipboard,
+    ) -> bool {
+        if self.read_only {
+            return false;
+        }
+        match clipboard.get() {
+            Some(text) => {
+                self.paste(widthdb, &text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Perform a cursor movement while keeping or starting a selection.
+    fn select_with<F>(&mut self, widthdb: &mut WidthDb, movement: F)
+    where
+        F: FnOnce(&mut Self, &mut WidthDb),
+    {
+        let anchor = self.selection_anchor.unwrap_or(self.cursor_idx);
+        movement(self, widthdb);
+        self.selection_anchor = Some(anchor);
+    }
+
+    pub fn select_left(&mut self, widthdb: &mut WidthDb) {
+        self.select_with(widthdb, Self::move_cursor_left);
+    }
+
+    pub fn select_right(&mut self, widthdb: &mut WidthDb) {
+        self.select_with(widthdb, Self::move_cursor_right);
+    }
+
+    pub fn select_left_a_word(&mut self, widthdb: &mut WidthDb) {
+        self.select_with(widthdb, Self::move_cursor_left_a_word);
+    }
+
+    pub fn select_right_a_word(&mut self, widthdb: &mut WidthDb) {
+        self.select_with(widthdb, Self::move_cursor_right_a_word);
+    }
+
+    pub fn select_to_start_of_line(&mut self, widthdb: &mut WidthDb) {
+        self.select_with(widthdb, Self::move_cursor_to_start_of_line);
+    }
+
+    pub fn select_to_end_of_line(&mut self, widthdb: &mut WidthDb) {
+        self.select_with(widthdb, Self::move_cursor_to_end_of_line);
+    }
+
+    pub fn select_up(&mut self, widthdb: &mut WidthDb) {
+        self.select_with(widthdb, Self::move_cursor_up);
+    }
+
+    pub fn select_down(&mut self, widthdb: &mut WidthDb) {
+        self.select_with(widthdb, Self::move_cursor_down);
+    }
+
+    ///////////////
+    // Kill ring //
+    ///////////////
+
+    /// Delete a range of the text and add it to the kill ring.
+    ///
+    /// Does nothing if the range is empty.
+    fn kill(&mut self, widthdb: &mut WidthDb, range: Range<usize>) {
+        if self.read_only || range.is_empty() {
+            return;
+        }
+
+        self.push_kill(self.text.slice(range.clone()).into_owned());
+        self.delete_range(widthdb, range);
+    }
+
+    fn push_kill(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        if self.kill_ring.len() >= KILL_RING_SIZE {
+            self.kill_ring.remove(0);
+        }
+        self.kill_ring.push(text);
+    }
+
+    /// Kill the text from the cursor to the end of its line.
+    ///
+    /// If the cursor is already at the end of its line, kill the newline
+    /// instead.
+    pub fn kill_to_end_of_line(&mut self, widthdb: &mut WidthDb) {
+        let (_, end) = self.cursor_line_range();
+        let end = if self.cursor_idx == end && end < self.text.len() {
+            end + 1 // utf-8 encodes '\n' as a single byte
+        } else {
+            end
+        };
+        self.kill(widthdb, self.cursor_idx..end);
+    }
+
+    /// Kill the text from the cursor to the end of the next word.
+    pub fn kill_word(&mut self, widthdb: &mut WidthDb) {
+        self.kill(widthdb, self.cursor_idx..self.word_end_after_cursor());
+    }
+
+    /// Kill the text from the start of the previous word to the cursor.
+    pub fn backward_kill_word(&mut self, widthdb: &mut WidthDb) {
+        self.kill(widthdb, self.word_start_before_cursor()..self.cursor_idx);
+    }
+
+    /// Insert the most recently killed text at the cursor position, replacing
+    /// the selection if there is one.
+    pub fn yank(&mut self, widthdb: &mut WidthDb) {
+        if self.read_only {
+            return;
+        }
+
+        let Some(index) = self.kill_ring.len().checked_sub(1) else {
+            return;
+        };
+
+        self.delete_selection(widthdb);
+        self.yank_entry(widthdb, index);
+    }
+
+    /// Replace the text inserted by the previous yank with the kill ring entry
+    /// before it.
+    ///
+    /// Does nothing if the previous operation was not a yank or yank pop.
+    pub fn yank_pop(&mut self, widthdb: &mut WidthDb) {
+        if self.read_only {
+            return;
+        }
+
+        let Some(yank) = self.last_yank.take() else {
+            return;
+        };
+
+        // Make sure the yanked text was not modified in the meantime
+        let unchanged = self.cursor_idx == yank.range.end
+            && self.text.get(yank.range.clone()).as_deref() == Some(&yank.text[..]);
+        if !unchanged {
+            return;
+        }
+
+        let index = match yank.index.checked_sub(1) {
+            Some(index) => index,
+            None => self.kill_ring.len() - 1,
+        };
+
+        self.selection_anchor = None;
+        self.cursor_idx = yank.range.start;
+        self.text.replace_range(yank.range, "");
+        self.yank_entry(widthdb, index);
+    }
+
+    fn yank_entry(&mut self, widthdb: &mut WidthDb, index: usize) {
+        let start = self.cursor_idx;
+        let text = self.filter_input(&self.kill_ring[index], start..start);
+        self.text.insert_str(start, &text);
+        self.cursor_idx += text.len();
+        self.record_cursor_col(widthdb);
+        self.last_yank = Some(Yank {
+            range: start..self.cursor_idx,
+            index,
+            text,
+        });
+    }
+
+    ////////////
+    // Search //
+    ////////////
+
+    /// The current search pattern, if any.
+    pub fn search(&self) -> Option<&str> {
+        self.search.as_deref()
+    }
+
+    /// Set the pattern to search for, or stop searching by passing `None`.
+    ///
+    /// Matches are found via plain substring search.
+    pub fn set_search(&mut self, pattern: Option<String>) {
+        self.search = pattern.filter(|p| !p.is_empty());
+    }
+
+    /// The ranges of all non-overlapping matches of the search pattern.
+    ///
+    /// These can be used to highlight matches via [`Editor::highlight`].
+    pub fn search_matches(&self) -> Vec<Range<usize>> {
+        let Some(pattern) = &self.search else {
+            return vec![];
+        };
+
+        self.text.matches(pattern)
+    }
+
+    /// The match that is currently selected, if any.
+    pub fn current_match(&self) -> Option<Range<usize>> {
+        let selection = self.selection()?;
+        self.search_matches().into_iter().find(|m| *m == selection)
+    }
+
+    fn select_match(&mut self, widthdb: &mut WidthDb, range: Range<usize>) {
+        self.selection_anchor = Some(range.start);
+        self.cursor_idx = range.end;
+        self.record_cursor_col(widthdb);
+    }
+
+    /// Select the next match after the cursor, wrapping around at the end of
+    /// the text.
+    ///
+    /// Returns `false` if there are no matches.
+    pub fn search_next(&mut self, widthdb: &mut WidthDb) -> bool {
+        let matches = self.search_matches();
+        let after = self.cursor_idx;
+        let next = matches
+            .iter()
+            .find(|m| m.start >= after)
+            .or_else(|| matches.first());
+
+        match next {
+            Some(m) => {
+                self.select_match(widthdb, m.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Select the previous match before the cursor or current match, wrapping
+    /// around at the start of the text.
+    ///
+    /// Returns `false` if there are no matches.
+    pub fn search_previous(&mut self, widthdb: &mut WidthDb) -> bool {
+        let matches = self.search_matches();
+        let before = match self.selection() {
+            Some(selection) => selection.start,
+            None => self.cursor_idx,
+        };
+        let previous = matches
+            .iter()
+            .rev()
+            .find(|m| m.end <= before)
+            .or_else(|| matches.last());
+
+        match previous {
+            Some(m) => {
+                self.select_match(widthdb, m.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace the currently selected match and select the next one.
+    ///
+    /// If no match is selected, only select the next match. Returns `true` if
+    /// a match was replaced.
+    pub fn replace_match(&mut self, widthdb: &mut WidthDb, replacement: &str) -> bool {
+        if self.read_only {
+            return false;
+        }
+
+        let Some(range) = self.current_match() else {
+            self.search_next(widthdb);
+            return false;
+        };
+
+        self.selection_anchor = None;
+        self.cursor_idx = range.start + replacement.len();
+        self.text.replace_range(range, replacement);
+        self.record_cursor_col(widthdb);
+        self.search_next(widthdb);
+        true
+    }
+
+    /// Replace all matches of the search pattern.
+    ///
+    /// Returns the amount of replaced matches.
+    pub fn replace_all(&mut self, widthdb: &mut WidthDb, replacement: &str) -> usize {
+        let matches = self.search_matches();
+        if self.read_only || matches.is_empty() {
+            return 0;
+        }
+
+        // Keep the cursor at the same position relative to the text around it
+        let mut cursor_idx = self.cursor_idx;
+        for m in matches.iter().rev() {
+            if m.end <= self.cursor_idx {
+                cursor_idx = cursor_idx - m.len() + replacement.len();
+            } else if m.start < self.cursor_idx {
+                cursor_idx = m.start + replacement.len();
+            }
+            self.text.replace_range(m.clone(), replacement);
+        }
+
+        self.selection_anchor = None;
+        self.cursor_idx = cursor_idx;
+        self.move_cursor_to_grapheme_boundary();
+        self.record_cursor_col(widthdb);
+        matches.len()
+    }
+
+    //////////////////
+    // Key handling //
+    //////////////////
+
+    /// Handle a key event using a standard set of key bindings.
+    ///
+    /// Supports cursor movement using the arrow keys, home and end, word-wise
+    /// movement with ctrl or alt, selection with shift, deletion, and the
+    /// common readline bindings. Enter submits the text while alt+enter and
+    /// shift+enter insert a newline. If the history is enabled, up and down
+    /// navigate it while the cursor is on the first or last line. While
+    /// completion candidates are offered, tab and the arrow keys cycle through
+    /// them, enter accepts and escape cancels the completion. If vi mode is
+    /// enabled, the key is first handled according to vi mode.
+    pub fn handle_key(&mut self, widthdb: &mut WidthDb, event: KeyEvent) -> EditorAction {
+        if event.kind == KeyEventKind::Release {
+            return EditorAction::Ignored;
+        }
+
+        #[cfg(feature = "vi")]
+        {
+            let text_len = self.text.len();
+            let cursor_idx = self.cursor_idx;
+            if self.handle_vi_key(widthdb, event) {
+                return if self.text.len() != text_len {
+                    EditorAction::TextChanged
+                } else if self.cursor_idx != cursor_idx {
+                    EditorAction::CursorMoved
+                } else {
+                    EditorAction::Handled
+                };
+            }
+        }
+
+        let shift = event.modifiers.contains(KeyModifiers::SHIFT);
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = event.modifiers.contains(KeyModifiers::ALT);
+
+        if self.completion.is_some() {
+            match event.code {
+                KeyCode::Tab | KeyCode::Down => self.next_completion(),
+                KeyCode::BackTab | KeyCode::Up => self.previous_completion(),
+                KeyCode::Esc => self.cancel_completion(),
+                KeyCode::Enter if self.accept_completion(widthdb) => {
+                    return EditorAction::TextChanged;
+                }
+                KeyCode::Enter => return EditorAction::Handled,
+                _ => self.cancel_completion(),
+            }
+            if self.completion.is_some() || event.code == KeyCode::Esc {
+                return EditorAction::Handled;
+            }
+        }
+
+        // Like in shells, the history is only navigated if the cursor can't
+        // move any further up or down
+        let history = match event.code {
+            KeyCode::Up if !shift && self.cursor_on_first_line() => self.history_previous(widthdb),
+            KeyCode::Down if !shift && self.cursor_on_last_line() => self.history_next(widthdb),
+            _ => false,
+        };
+        if history {
+            return EditorAction::TextChanged;
+        }
+
+        let movement: fn(&mut Self, &mut WidthDb) = match (event.code, ctrl || alt) {
+            (KeyCode::Left, false) => Self::move_cursor_left,
+            (KeyCode::Right, false) => Self::move_cursor_right,
+            (KeyCode::Left, true) => Self::move_cursor_left_a_word,
+            (KeyCode::Right, true) => Self::move_cursor_right_a_word,
+            (KeyCode::Up, false) => Self::move_cursor_up,
+            (KeyCode::Down, false) => Self::move_cursor_down,
+            (KeyCode::Home, false) => Self::move_cursor_to_start_of_line,
+            (KeyCode::End, false) => Self::move_cursor_to_end_of_line,
+            (KeyCode::Char('b'), true) if ctrl => Self::move_cursor_left,
+            (KeyCode::Char('f'), true) if ctrl => Self::move_cursor_right,
+            (KeyCode::Char('b'), true) if alt => Self::move_cursor_left_a_word,
+            (KeyCode::Char('f'), true) if alt => Self::move_cursor_right_a_word,
+            (KeyCode::Char('a'), true) if ctrl => Self::move_cursor_to_start_of_line,
+            (KeyCode::Char('e'), true) if ctrl => Self::move_cursor_to_end_of_line,
+            _ => return self.handle_editing_key(widthdb, event.code, shift, ctrl, alt),
+        };
+
+        if shift {
+            self.select_with(widthdb, movement);
+        } else {
+            movement(self, widthdb);
+        }
+        EditorAction::CursorMoved
+    }
+
+    fn handle_editing_key(
+        &mut self,
+        widthdb: &mut WidthDb,
+        code: KeyCode,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+    ) -> EditorAction {
+        if self.read_only {
+            return EditorAction::Ignored;
+        }
+
+        match code {
+            KeyCode::Enter if shift || alt => self.insert_newline(widthdb),
+            KeyCode::Enter => return EditorAction::Submit,
+            KeyCode::Backspace if ctrl || alt => self.backward_kill_word(widthdb),
+            KeyCode::Backspace => self.backspace(widthdb),
+            KeyCode::Delete if ctrl || alt => self.kill_word(widthdb),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Tab => self.insert_tab(widthdb),
+            KeyCode::BackTab => self.dedent(widthdb),
+            KeyCode::Char('d') if ctrl => self.delete(),
+            KeyCode::Char('h') if ctrl => self.backspace(widthdb),
+            KeyCode::Char('k') if ctrl => self.kill_to_end_of_line(widthdb),
+            KeyCode::Char('t') if ctrl => self.transpose_graphemes(widthdb),
+            KeyCode::Char('u') if ctrl => self.delete_to_start_of_line(widthdb),
+            KeyCode::Char('w') if ctrl => self.backward_kill_word(widthdb),
+            KeyCode::Char('y') if ctrl => self.yank(widthdb),
+            KeyCode::Char('d') if alt => self.kill_word(widthdb),
+            KeyCode::Char('t') if alt => self.transpose_words(widthdb),
+            KeyCode::Char('y') if alt => self.yank_pop(widthdb),
+            KeyCode::Char(ch) if !ctrl && !alt => self.insert_char(widthdb, ch),
+            _ => return EditorAction::Ignored,
+        }
+        EditorAction::TextChanged
+    }
+
+    /// Handle a key event like [`Self::handle_key`], additionally binding
+    /// ctrl+c, ctrl+x and ctrl+v to [`Self::copy`], [`Self::cut`] and
+    /// [`Self::paste_from_clipboard`].
+    ///
+    /// Ctrl+c is ignored if nothing is selected, so it can still be used to
+    /// exit the application.
+    pub fn handle_key_with_clipboard(
+        &mut self,
+        widthdb: &mut WidthDb,
+        event: KeyEvent,
+        clipboard: &mut dyn Clipboard,
+    ) -> EditorAction {
+        if event.kind == KeyEventKind::Release || event.modifiers != KeyModifiers::CONTROL {
+            return self.handle_key(widthdb, event);
+        }
+
+        match event.code {
+            KeyCode::Char('c') if self.copy(clipboard) => EditorAction::Handled,
+            KeyCode::Char('c') => EditorAction::Ignored,
+            KeyCode::Char('x') if self.cut(widthdb, clipboard) => EditorAction::TextChanged,
+            KeyCode::Char('x') => EditorAction::Ignored,
+            KeyCode::Char('v') if self.paste_from_clipboard(widthdb, clipboard) => {
+                EditorAction::TextChanged
+            }
+            KeyCode::Char('v') => EditorAction::Ignored,
+            _ => self.handle_key(widthdb, event),
+        }
+    }
+
+    /// Handle a terminal event using the key bindings described in
+    /// [`Self::handle_key`].
+    ///
+    /// Pasted text is inserted via [`Self::paste`].
+    pub fn handle_event(&mut self, widthdb: &mut WidthDb, event: &Event) -> EditorAction {
+        match event {
+            Event::Key(event) => self.handle_key(widthdb, *event),
+            Event::Paste(_) if self.read_only => EditorAction::Ignored,
+            Event::Paste(text) => {
+                self.paste(widthdb, text);
+                EditorAction::TextChanged
+            }
+            _ => EditorAction::Ignored,
+        }
+    }
+
+    /// Handle a terminal event like [`Self::handle_event`], using the key
+    /// bindings described in [`Self::handle_key_with_clipboard`].
+    pub fn handle_event_with_clipboard(
+        &mut self,
+        widthdb: &mut WidthDb,
+        event: &Event,
+        clipboard: &mut dyn Clipboard,
+    ) -> EditorAction {
+        match event {
+            Event::Key(event) => self.handle_key_with_clipboard(widthdb, *event, clipboard),
+            event => self.handle_event(widthdb, event),
+        }
+    }
+
+    /// Index of the first visible row when the editor was last rendered.
+    ///
+    /// If the editor is higher than the available space, it scrolls such that
+    /// the cursor is always visible.
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll_offset
+    }
+
+    pub fn last_cursor_pos(&self) -> Pos {
         self.last_cursor_pos
     }
 
     pub fn widget(&mut self) -> Editor<'_> {
         Editor {
-            highlighted: Styled::new_plain(&self.text),
+            highlighted: OnceLock::new(),
             hidden: None,
             focus: true,
+            selection_style: Theme::default().selection,
+            placeholder: None,
+            line_numbers: false,
+            line_number_style: Theme::default().dim,
+            mask: None,
+            continuation: Styled::default(),
+            composition: String::new(),
+            composition_style: Style::new().underlined(),
             state: self,
         }
     }
@@ -342,9 +1604,28 @@ impl Default for EditorState {
 #[derive(Debug)]
 pub struct Editor<'a> {
     state: &'a mut EditorState,
-    highlighted: Styled,
+    /// The highlighted text, or empty if it was not yet needed.
+    ///
+    /// Unless explicitly highlighted, only the visible lines are built.
+    highlighted: OnceLock<Styled>,
     pub hidden: Option<Styled>,
     pub focus: bool,
+    pub selection_style: Style,
+    /// Displayed (dimmed) while the text is empty and not hidden.
+    pub placeholder: Option<Styled>,
+    /// Whether line numbers should be displayed to the left of the text.
+    pub line_numbers: bool,
+    pub line_number_style: Style,
+    /// Character displayed instead of each grapheme, except for newlines.
+    pub mask: Option<char>,
+    /// Displayed at the start of rows continuing a soft-wrapped line.
+    pub continuation: Styled,
+    /// Text the user is currently composing using an input method, or empty
+    /// if no composition is in progress.
+    ///
+    /// It is displayed at the cursor but not part of the editor's text.
+    pub composition: String,
+    pub composition_style: Style,
 }
 
 impl Editor<'_> {
@@ -353,15 +1634,17 @@ impl Editor<'_> {
     }
 
     pub fn text(&self) -> &Styled {
-        &self.highlighted
+        self.highlighted
+            .get_or_init(|| Styled::new_plain(self.state.text.as_str()))
     }
 
     pub fn highlight<F>(&mut self, highlight: F)
     where
         F: FnOnce(&str) -> Styled,
     {
-        self.highlighted = highlight(&self.state.text);
-        assert_eq!(self.state.text, self.highlighted.text());
+        let highlighted = highlight(self.state.text.as_str());
+        assert_eq!(self.state.text.as_str(), highlighted.text());
+        self.highlighted = OnceLock::from(highlighted);
     }
 
     pub fn with_highlight<F>(mut self, highlight: F) -> Self
@@ -383,7 +1666,7 @@ impl Editor<'_> {
     }
 
     pub fn with_hidden_default_placeholder(self) -> Self {
-        self.with_hidden(("<hidden>", Style::new().grey().italic()))
+        self.with_hidden(("<hidden>", Theme::default().placeholder))
     }
 
     pub fn with_focus(mut self, active: bool) -> Self {
@@ -391,95 +1674,419 @@ impl Editor<'_> {
         self
     }
 
-    fn wrapped_cursor(cursor_idx: usize, break_indices: &[usize]) -> (usize, usize) {
-        let mut row = 0;
-        let mut line_idx = cursor_idx;
+    pub fn with_selection_style(mut self, style: Style) -> Self {
+        self.selection_style = style;
+        self
+    }
 
-        for break_idx in break_indices {
-            if cursor_idx < *break_idx {
-                break;
-            } else {
-                row += 1;
-                line_idx = cursor_idx - break_idx;
-            }
+    pub fn with_placeholder<S: Into<Styled>>(mut self, placeholder: S) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn with_line_numbers(mut self, active: bool) -> Self {
+        self.line_numbers = active;
+        self
+    }
+
+    pub fn with_line_number_style(mut self, style: Style) -> Self {
+        self.line_number_style = style;
+        self
+    }
+
+    /// Apply the selection and line number styles of a theme.
+    pub fn with_theme(self, theme: &Theme) -> Self {
+        self.with_selection_style(theme.selection)
+            .with_line_number_style(theme.dim)
+    }
+
+    /// Set the marker displayed at the start of rows continuing a
+    /// soft-wrapped line, for example `"↪ "`.
+    ///
+    /// A marker consisting only of spaces indents continuation rows instead.
+    /// Continuation rows are shortened by the width of the marker.
+    pub fn with_continuation<S: Into<Styled>>(mut self, continuation: S) -> Self {
+        self.continuation = continuation.into();
+        self
+    }
+
+    /// Display in-progress input method composition text at the cursor.
+    ///
+    /// The text is not inserted into the [`EditorState`]. Once the input method
+    /// commits the composition, insert the committed text normally and stop
+    /// passing it to this function. The terminal cursor is placed after the
+    /// composition so the input method's popup appears next to it.
+    pub fn with_composition<S: Into<String>>(mut self, composition: S) -> Self {
+        self.composition = composition.into();
+        self
+    }
+
+    pub fn with_composition_style(mut self, style: Style) -> Self {
+        self.composition_style = style;
+        self
+    }
+
+    fn continuation_width(&self, widthdb: &mut WidthDb) -> usize {
+        self.continuation.width(widthdb)
+    }
+
+    /// The gutter used to display line numbers.
+    ///
+    /// Unlike wrapping the editor in a [`Gutter`], this takes into account how
+    /// far the editor is scrolled.
+    fn gutter(&self) -> Gutter<()> {
+        Gutter::new(()).with_style(self.line_number_style)
+    }
+
+    /// Width of the line number gutter, including a single column of space
+    /// between the line numbers and the text.
+    fn gutter_width(&self, widthdb: &mut WidthDb) -> u16 {
+        if !self.line_numbers {
+            return 0;
+        }
+
+        let lines = match self.hidden {
+            Some(_) => 1,
+            None => self.state.text.len_lines(),
+        };
+        self.gutter().width_for_lines(widthdb, lines)
+    }
+
+    /// Draw the placeholder if the text is empty.
+    ///
+    /// The placeholder is not taken into account when calculating the size of
+    /// the editor, so it is cut off if it doesn't fit.
+    fn draw_placeholder(&self, frame: &mut Frame) {
+        if !self.state.text.is_empty() || !self.composition.is_empty() || self.hidden.is_some() {
+            return;
+        }
+        let Some(placeholder) = &self.placeholder else {
+            return;
+        };
+
+        let placeholder = placeholder
+            .styled_grapheme_indices()
+            .fold(Styled::default(), |acc, (_, style, g)| {
+                acc.then(g, style.dim())
+            });
+
+        let width = frame.size().width.saturating_sub(1) as usize;
+        let indices = wrap(frame.widthdb(), placeholder.text(), width, 0);
+        for (i, row) in placeholder
+            .split_at_indices(&indices)
+            .into_iter()
+            .enumerate()
+        {
+            frame.write(Pos::new(0, i as i32), row);
+        }
+    }
+
+    /// Like [`Self::with_hidden`], but instead of a placeholder, each grapheme
+    /// is replaced by the mask character.
+    ///
+    /// Unlike hidden text, masked text can be navigated normally.
+    pub fn with_mask(mut self, mask: char) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The text to display instead of a grapheme.
+    fn mask_grapheme(mask: char, grapheme: &str) -> String {
+        // Keep newlines so lines stay intact
+        if grapheme.ends_with('\n') {
+            "\n".to_string()
+        } else {
+            mask.to_string()
+        }
+    }
+
+    /// Convert an index into the state's text into an index into the masked
+    /// line starting at `line_start`, i.e. the displayed line without the
+    /// composition.
+    fn masked_idx(&self, line_start: usize, idx: usize) -> usize {
+        match self.mask {
+            Some(mask) => self
+                .state
+                .text
+                .slice(line_start..idx)
+                .graphemes(true)
+                .map(|g| Self::mask_grapheme(mask, g).len())
+                .sum(),
+            None => idx - line_start,
+        }
+    }
+
+    /// Convert an index into the state's text into a line and an index into
+    /// that displayed line.
+    ///
+    /// Indices at or after the cursor are placed after the composition.
+    fn display_idx(&self, idx: usize) -> (usize, usize) {
+        let (line, range) = self.state.text.line_at(idx);
+        let masked_idx = self.masked_idx(range.start, idx);
+        if idx >= self.state.cursor_idx && line == self.cursor_line() {
+            (line, masked_idx + self.composition().len())
+        } else {
+            (line, masked_idx)
         }
+    }
+
+    fn cursor_line(&self) -> usize {
+        self.state.text.line_at(self.state.cursor_idx).0
+    }
+
+    /// The composition text as it is displayed.
+    fn composition(&self) -> String {
+        match self.mask {
+            Some(mask) => self
+                .composition
+                .graphemes(true)
+                .map(|g| Self::mask_grapheme(mask, g))
+                .collect(),
+            None => self.composition.clone(),
+        }
+    }
 
-        (row, line_idx)
+    /// The hidden placeholder, if it is displayed instead of the text.
+    fn shown_hidden(&self) -> Option<&Styled> {
+        self.hidden.as_ref().filter(|_| !self.state.text.is_empty())
     }
 
-    fn indices(&self, widthdb: &mut WidthDb, max_width: Option<u16>) -> Vec<usize> {
+    /// A displayed line, i.e. the highlighted line with the mask, the
+    /// selection style and the composition applied.
+    ///
+    /// The line includes its trailing newline.
+    fn displayed_line(&self, line: usize) -> Styled {
+        if let Some(hidden) = self.shown_hidden() {
+            return hidden.substring(line_ranges(hidden.text())[line].clone());
+        }
+
+        let range = self.state.text.line_range(line);
+        let mut text = match self.highlighted.get() {
+            Some(highlighted) => highlighted.substring(range.clone()),
+            None => Styled::new_plain(self.state.text.slice(range.clone())),
+        };
+
+        if let Some(mask) = self.mask {
+            text = text.redact(mask);
+        }
+
+        if let Some(selection) = self.state.selection() {
+            let start = selection.start.clamp(range.start, range.end);
+            let end = selection.end.clamp(range.start, range.end);
+            let start = self.masked_idx(range.start, start);
+            let end = self.masked_idx(range.start, end);
+            text = text.style_range(start..end, self.selection_style);
+        }
+
+        if !self.composition.is_empty() && line == self.cursor_line() {
+            let (left, right) = text.split_at(self.masked_idx(range.start, self.state.cursor_idx));
+            text = left
+                .then(self.composition(), self.composition_style)
+                .and_then(right);
+        }
+
+        text
+    }
+
+    fn rows(&self, widthdb: &mut WidthDb, max_width: Option<u16>) -> Vec<Row> {
         let max_width = max_width
             // One extra column for cursor
             .map(|w| w.saturating_sub(1) as usize)
             .unwrap_or(usize::MAX);
-        let text = self.hidden.as_ref().unwrap_or(&self.highlighted);
-        wrap(widthdb, text.text(), max_width)
-    }
+        let indent = self.continuation_width(widthdb);
+
+        if let Some(hidden) = self.shown_hidden() {
+            let text = hidden.text();
+            return line_ranges(text)
+                .into_iter()
+                .enumerate()
+                .flat_map(|(i, range)| wrap_line(widthdb, i, &text[range], max_width, indent))
+                .collect();
+        }
 
-    fn rows(&self, indices: &[usize]) -> Vec<Styled> {
-        let text = match self.hidden.as_ref() {
-            Some(hidden) if !self.highlighted.text().is_empty() => hidden,
-            _ => &self.highlighted,
-        };
-        text.clone().split_at_indices(indices)
+        if self.mask.is_some() {
+            return (0..self.state.text.len_lines())
+                .flat_map(|i| {
+                    let text = self.displayed_line(i);
+                    wrap_line(widthdb, i, text.text(), max_width, indent)
+                })
+                .collect();
+        }
+
+        // Without a mask, only the line containing the composition differs
+        // from the state's text
+        let mut rows = self.state.text.wrap(widthdb, max_width, indent);
+        if !self.composition.is_empty() {
+            let line = self.cursor_line();
+            let text = self.displayed_line(line);
+            let start = rows.partition_point(|r| r.line < line);
+            let end = rows.partition_point(|r| r.line <= line);
+            let wrapped = wrap_line(widthdb, line, text.text(), max_width, indent);
+            rows.splice(start..end, wrapped);
+        }
+        rows
     }
 
-    fn cursor(&self, widthdb: &mut WidthDb, width: u16, indices: &[usize], rows: &[Styled]) -> Pos {
+    /// Position of a text index relative to the top left corner of the text.
+    fn text_pos(&self, widthdb: &mut WidthDb, width: u16, idx: usize, rows: &[Row]) -> Pos {
         if self.hidden.is_some() {
             return Pos::new(0, 0);
         }
 
-        let (cursor_row, cursor_line_idx) = Self::wrapped_cursor(self.state.cursor_idx, indices);
-        let cursor_col = widthdb.width(rows[cursor_row].text().split_at(cursor_line_idx).0);
+        let (line, line_idx) = self.display_idx(idx);
+        let row = rows
+            .partition_point(|r| (r.line, r.range.start) <= (line, line_idx))
+            .saturating_sub(1);
+        let start = rows[row].range.start;
+        let text = self.displayed_line(line);
+        let mut col = widthdb.width(&text.text()[start..line_idx]);
+        if start > 0 {
+            col += self.continuation_width(widthdb);
+        }
 
         // Ensure the cursor is always visible
-        let cursor_col = cursor_col.min(width.saturating_sub(1).into());
+        let col = col.min(width.saturating_sub(1).into());
 
-        let cursor_row: i32 = cursor_row.try_into().unwrap_or(i32::MAX);
-        let cursor_col: i32 = cursor_col.try_into().unwrap_or(i32::MAX);
-        Pos::new(cursor_col, cursor_row)
+        let row: i32 = row.try_into().unwrap_or(i32::MAX);
+        let col: i32 = col.try_into().unwrap_or(i32::MAX);
+        Pos::new(col, row)
     }
 }
 
+/// Ranges of the lines of a text, including their trailing newlines.
+///
+/// If the text ends with a newline, its last line is empty.
+fn line_ranges(text: &str) -> Vec<Range<usize>> {
+    let starts = iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1));
+    let ends = text
+        .match_indices('\n')
+        .map(|(i, _)| i + 1)
+        .chain(iter::once(text.len()));
+    starts.zip(ends).map(|(start, end)| start..end).collect()
+}
+
 impl<E> Widget<E> for Editor<'_> {
     fn size(
         &self,
         widthdb: &mut WidthDb,
         max_width: Option<u16>,
-        _max_height: Option<u16>,
+        max_height: Option<u16>,
     ) -> Result<Size, E> {
-        let indices = self.indices(widthdb, max_width);
-        let rows = self.rows(&indices);
+        let gutter_width = self.gutter_width(widthdb);
+        let max_width = max_width.map(|w| w.saturating_sub(gutter_width));
+        let rows = self.rows(widthdb, max_width);
 
+        let continuation_width = self.continuation_width(widthdb);
         let width = rows
             .iter()
-            .map(|row| widthdb.width(row.text()))
+            .map(|row| {
+                if row.range.start > 0 {
+                    row.width + continuation_width
+                } else {
+                    row.width
+                }
+            })
             .max()
             .unwrap_or(0)
             // One extra column for cursor
-            .saturating_add(1);
-        let height = rows.len();
+            .saturating_add(1)
+            .saturating_add(gutter_width.into());
+        let height: u16 = rows.len().try_into().unwrap_or(u16::MAX);
+        let height = match max_height {
+            Some(max_height) => height.min(max_height),
+            None => height,
+        };
 
         let width: u16 = width.try_into().unwrap_or(u16::MAX);
-        let height: u16 = height.try_into().unwrap_or(u16::MAX);
         Ok(Size::new(width, height))
     }
 
     fn draw(self, frame: &mut Frame) -> Result<(), E> {
-        let size = frame.size();
-        let indices = self.indices(frame.widthdb(), Some(size.width));
-        let rows = self.rows(&indices);
-        let cursor = self.cursor(frame.widthdb(), size.width, &indices, &rows);
-
-        for (i, row) in rows.into_iter().enumerate() {
-            frame.write(Pos::new(0, i as i32), row);
+        let gutter_width = self.gutter_width(frame.widthdb());
+        let mut size = frame.size();
+        size.width = size.width.saturating_sub(gutter_width);
+
+        let rows = self.rows(frame.widthdb(), Some(size.width));
+        let cursor_idx = self.state.cursor_idx;
+        let mut cursor = self.text_pos(frame.widthdb(), size.width, cursor_idx, &rows);
+        let word_start = self.state.word_under_cursor().start;
+        let mut word_pos = self.text_pos(frame.widthdb(), size.width, word_start, &rows);
+
+        // Scroll so the cursor is visible, without leaving empty rows at the
+        // bottom if the text doesn't need them
+        let height = (size.height as usize).max(1);
+        let cursor_row = cursor.y as usize;
+        let mut scroll_offset = self.state.scroll_offset;
+        scroll_offset = scroll_offset.min(rows.len().saturating_sub(height));
+        scroll_offset = scroll_offset.min(cursor_row);
+        scroll_offset = scroll_offset.max((cursor_row + 1).saturating_sub(height));
+        self.state.scroll_offset = scroll_offset;
+
+        let visible = scroll_offset..(scroll_offset + height).min(rows.len());
+        let scroll_offset: i32 = scroll_offset.try_into().unwrap_or(i32::MAX);
+        cursor.y -= scroll_offset;
+        word_pos.y -= scroll_offset;
+
+        if self.line_numbers {
+            let hidden_lines = rows[..visible.start]
+                .iter()
+                .filter(|r| r.range.start == 0)
+                .count();
+            let starts = rows[visible.clone()]
+                .iter()
+                .map(|r| r.range.start == 0)
+                .collect::<Vec<_>>();
+            self.gutter().with_first_line(hidden_lines + 1).draw_rows(
+                frame,
+                gutter_width,
+                &starts,
+                0,
+            );
         }
 
+        frame.push(Pos::new(gutter_width.into(), 0), size);
+        let continuation_width = self.continuation_width(frame.widthdb());
+        let continuation_width: i32 = continuation_width.try_into().unwrap_or(i32::MAX);
+        let mut line: Option<(usize, Styled)> = None;
+        for (y, row) in rows[visible].iter().enumerate() {
+            let text = match &line {
+                Some((i, text)) if *i == row.line => text,
+                _ => &line.insert((row.line, self.displayed_line(row.line))).1,
+            };
+            let text = text.substring(row.range.clone());
+            let y = y as i32;
+            if row.range.start == 0 {
+                frame.write(Pos::new(0, y), text);
+            } else {
+                frame.write(Pos::new(0, y), self.continuation.clone());
+                frame.write(Pos::new(continuation_width, y), text);
+            }
+        }
+        self.draw_placeholder(frame);
         if self.focus {
             frame.set_cursor(Some(cursor));
+            #[cfg(feature = "vi")]
+            if let Some(mode) = self.state.vi_mode() {
+                frame.set_cursor_style(match mode {
+                    ViMode::Insert => crate::CursorStyle::SteadyBar,
+                    ViMode::Normal | ViMode::Visual => crate::CursorStyle::SteadyBlock,
+                });
+            }
         }
-        self.state.last_cursor_pos = cursor;
+        self.state.last_word_pos = frame.local_to_global(word_pos);
+        frame.pop();
+
+        self.state.last_cursor_pos = cursor + Pos::new(gutter_width.into(), 0);
 
         Ok(())
     }
 }
+
+impl LineStarts for Editor<'_> {
+    fn line_starts(&self, widthdb: &mut WidthDb, max_width: Option<u16>) -> Vec<bool> {
+        self.rows(widthdb, max_width)
+            .into_iter()
+            .map(|r| r.range.start == 0)
+            .collect()
+    }
+}
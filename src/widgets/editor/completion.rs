@@ -0,0 +1,126 @@
+//! Completing the word under the cursor from a list of candidates.
+
+use std::ops::Range;
+
+use crate::widgets::CompletionMenu;
+use crate::{Pos, WidthDb};
+
+use super::EditorState;
+
+#[derive(Debug, Clone)]
+pub(super) struct Completion {
+    candidates: Vec<String>,
+    selected: usize,
+    /// Range of the word the candidates would replace.
+    range: Range<usize>,
+}
+
+impl EditorState {
+    /// Range of the word the cursor is currently in or directly adjacent to,
+    /// where a word is a sequence of non-whitespace graphemes.
+    ///
+    /// The range is empty if there is no such word.
+    pub fn word_under_cursor(&self) -> Range<usize> {
+        self.word_at(self.cursor_idx)
+    }
+
+    /// Screen position of the start of the word under the cursor when the
+    /// editor was last rendered.
+    ///
+    /// Unlike [`Self::last_cursor_pos`], this position is relative to the top
+    /// left corner of the screen, not the editor.
+    pub fn last_word_pos(&self) -> Pos {
+        self.last_word_pos
+    }
+
+    /// Offer candidates for completing the word under the cursor.
+    ///
+    /// Passing an empty list of candidates cancels the completion.
+    pub fn set_completions(&mut self, candidates: Vec<String>) {
+        self.completion = if candidates.is_empty() {
+            None
+        } else {
+            Some(Completion {
+                candidates,
+                selected: 0,
+                range: self.word_under_cursor(),
+            })
+        };
+    }
+
+    pub fn cancel_completion(&mut self) {
+        self.completion = None;
+    }
+
+    /// The current completion candidates.
+    pub fn completions(&self) -> &[String] {
+        match &self.completion {
+            Some(completion) => &completion.candidates,
+            None => &[],
+        }
+    }
+
+    /// Index of the selected completion candidate, if there are any.
+    pub fn selected_completion(&self) -> Option<usize> {
+        self.completion.as_ref().map(|c| c.selected)
+    }
+
+    /// Select the next completion candidate, wrapping around at the end.
+    pub fn next_completion(&mut self) {
+        if let Some(completion) = &mut self.completion {
+            completion.selected = (completion.selected + 1) % completion.candidates.len();
+        }
+    }
+
+    /// Select the previous completion candidate, wrapping around at the
+    /// start.
+    pub fn previous_completion(&mut self) {
+        if let Some(completion) = &mut self.completion {
+            let len = completion.candidates.len();
+            completion.selected = (completion.selected + len - 1) % len;
+        }
+    }
+
+    /// Replace the word the completion was started for with the selected
+    /// candidate and end the completion.
+    ///
+    /// The candidate is inserted like [`Self::insert_str`], so it is subject
+    /// to the input filter and maximum length.
+    ///
+    /// Returns `false` if there was no completion or the text changed in a way
+    /// that makes the word range invalid.
+    pub fn accept_completion(&mut self, widthdb: &mut WidthDb) -> bool {
+        if self.read_only {
+            return false;
+        }
+
+        let Some(completion) = self.completion.take() else {
+            return false;
+        };
+
+        let range = completion.range;
+        let valid = self.text.get(range.clone()).is_some();
+        if !valid {
+            return false;
+        }
+
+        self.selection_anchor = Some(range.start);
+        self.cursor_idx = range.end;
+        self.insert_str(widthdb, &completion.candidates[completion.selected]);
+        true
+    }
+
+    /// Create a [`CompletionMenu`] showing the current candidates on top of an
+    /// inner widget, anchored at [`Self::last_word_pos`].
+    ///
+    /// The inner widget should contain the editor and enough space around it
+    /// for the menu to be displayed, for example the whole screen.
+    pub fn completion_menu<I>(&self, inner: I) -> CompletionMenu<I> {
+        CompletionMenu::new(
+            inner,
+            self.completions().to_vec(),
+            self.selected_completion(),
+            self.last_word_pos,
+        )
+    }
+}
@@ -0,0 +1,138 @@
+//! Editing the text of an [`EditorState`] in an external editor.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, io, process};
+
+use crate::Terminal;
+
+use super::EditorState;
+
+/// How often to try a different file name if the temporary file exists.
+const TEMP_FILE_ATTEMPTS: u32 = 100;
+
+/// The editor command to run, which may contain arguments.
+fn editor_command() -> String {
+    env::var("VISUAL")
+        .ok()
+        .filter(|c| !c.trim().is_empty())
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|c| !c.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Run the editor command on a file.
+///
+/// Like git, the command is run through the shell with the path as separate
+/// argument, so both the command and the path may contain spaces.
+#[cfg(unix)]
+fn editor_process(command: &str, path: &Path) -> Command {
+    let mut process = Command::new("sh");
+    process
+        .arg("-c")
+        .arg(format!("{command} \"$1\""))
+        .arg(command)
+        .arg(path);
+    process
+}
+
+/// Run the editor command on a file.
+#[cfg(not(unix))]
+fn editor_process(command: &str, path: &Path) -> Command {
+    let mut parts = command.split_whitespace();
+    let mut process = Command::new(parts.next().unwrap_or("vi"));
+    process.args(parts).arg(path);
+    process
+}
+
+fn temp_file_path(attempt: u32) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let name = format!("toss-editor-{}-{nanos}-{attempt}.txt", process::id());
+    env::temp_dir().join(name)
+}
+
+/// Create a new temporary file that only the current user can access.
+///
+/// Existing files are never opened, so the file can't be replaced by a
+/// symlink in advance.
+fn create_temp_file() -> io::Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    for attempt in 0..TEMP_FILE_ATTEMPTS {
+        let path = temp_file_path(attempt);
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "failed to create a unique temporary file",
+    ))
+}
+
+fn run_editor(text: &str) -> io::Result<String> {
+    let (path, mut file) = create_temp_file()?;
+    let written = file.write_all(text.as_bytes());
+    drop(file);
+
+    let result =
+        written.and_then(
+            |()| match editor_process(&editor_command(), &path).status()? {
+                status if status.success() => fs::read_to_string(&path),
+                status => Err(io::Error::other(format!("editor exited with {status}"))),
+            },
+        );
+    let _ = fs::remove_file(&path);
+    result
+}
+
+impl EditorState {
+    /// Let the user edit the text in an external editor.
+    ///
+    /// The editor is determined by the `VISUAL` and `EDITOR` environment
+    /// variables, falling back to `vi`, and run through the shell on Unix.
+    /// While it is running, the terminal is suspended. Afterwards, the terminal
+    /// is unsuspended and the text is replaced by the contents of the edited
+    /// file. A single trailing newline added by the editor is removed.
+    ///
+    /// If the editor can't be started or exits unsuccessfully, the text is
+    /// left unchanged and an error is returned. If the editor state is
+    /// read-only, the text can be viewed but changes are discarded.
+    pub fn edit_externally(&mut self, terminal: &mut Terminal) -> io::Result<()> {
+        let text = self.text.to_string();
+
+        terminal.suspend()?;
+        let result = run_editor(&text);
+        terminal.unsuspend()?;
+        let mut new_text = result?;
+        if self.read_only {
+            return Ok(());
+        }
+
+        if !text.ends_with('\n') && new_text.ends_with('\n') {
+            new_text.pop();
+            if new_text.ends_with('\r') {
+                new_text.pop();
+            }
+        }
+
+        let widthdb = terminal.widthdb();
+        self.set_text(widthdb, new_text);
+        self.set_cursor_byte_idx(widthdb, usize::MAX);
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+//! Shell-like prompt history for [`EditorState`].
+
+use std::mem;
+
+use crate::WidthDb;
+
+use super::EditorState;
+
+#[derive(Debug, Clone)]
+pub(super) struct History {
+    /// Previously submitted entries, oldest first.
+    entries: Vec<String>,
+    max_len: usize,
+    /// Index of the entry currently being displayed, or `None` if the user is
+    /// editing a new entry.
+    position: Option<usize>,
+    /// The new entry, saved while navigating the history.
+    draft: String,
+}
+
+impl History {
+    fn new(max_len: usize) -> Self {
+        Self {
+            entries: vec![],
+            max_len,
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    fn truncate(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.max_len);
+        self.entries.drain(..excess);
+    }
+}
+
+impl EditorState {
+    /// Enable the history with the given maximum amount of entries, or
+    /// disable it by passing `None`.
+    ///
+    /// Changing the size limit of an enabled history keeps the most recent
+    /// entries.
+    pub fn set_history_size(&mut self, max_len: Option<usize>) {
+        match (&mut self.history, max_len) {
+            (Some(history), Some(max_len)) => {
+                history.max_len = max_len;
+                history.position = None;
+                history.truncate();
+            }
+            (history, max_len) => *history = max_len.map(History::new),
+        }
+    }
+
+    pub fn with_history_size(mut self, max_len: usize) -> Self {
+        self.set_history_size(Some(max_len));
+        self
+    }
+
+    /// The history entries, oldest first.
+    pub fn history(&self) -> &[String] {
+        match &self.history {
+            Some(history) => &history.entries,
+            None => &[],
+        }
+    }
+
+    /// Add an entry to the history.
+    ///
+    /// Empty entries are ignored. If the entry is already present in the
+    /// history, it is moved to the end instead of being added again. Does
+    /// nothing if the history is disabled.
+    pub fn push_history(&mut self, entry: String) {
+        let Some(history) = &mut self.history else {
+            return;
+        };
+
+        history.position = None;
+        if entry.is_empty() {
+            return;
+        }
+
+        history.entries.retain(|e| *e != entry);
+        history.entries.push(entry);
+        history.truncate();
+    }
+
+    /// Clear the editor and return its text, adding it to the history.
+    ///
+    /// If the editor is read-only, the text is returned without clearing the
+    /// editor or adding it to the history.
+    pub fn submit(&mut self) -> String {
+        let text = self.text.to_string();
+        if self.read_only {
+            return text;
+        }
+
+        self.push_history(text.clone());
+        self.clear();
+        text
+    }
+
+    /// Replace the text with the previous history entry.
+    ///
+    /// Returns `false` if there is no previous entry.
+    pub fn history_previous(&mut self, widthdb: &mut WidthDb) -> bool {
+        if self.read_only {
+            return false;
+        }
+
+        let Some(history) = &mut self.history else {
+            return false;
+        };
+
+        let position = match history.position {
+            Some(0) => return false,
+            Some(position) => position - 1,
+            None => match history.entries.len().checked_sub(1) {
+                Some(position) => {
+                    history.draft = self.text.to_string();
+                    position
+                }
+                None => return false,
+            },
+        };
+
+        history.position = Some(position);
+        let text = history.entries[position].clone();
+        self.set_history_text(widthdb, text);
+        true
+    }
+
+    /// Replace the text with the next history entry, or the new entry that
+    /// was being edited before navigating the history.
+    ///
+    /// Returns `false` if the history is not being navigated.
+    pub fn history_next(&mut self, widthdb: &mut WidthDb) -> bool {
+        if self.read_only {
+            return false;
+        }
+
+        let Some(history) = &mut self.history else {
+            return false;
+        };
+        let Some(position) = history.position else {
+            return false;
+        };
+
+        let text = if position + 1 < history.entries.len() {
+            history.position = Some(position + 1);
+            history.entries[position + 1].clone()
+        } else {
+            history.position = None;
+            mem::take(&mut history.draft)
+        };
+        self.set_history_text(widthdb, text);
+        true
+    }
+
+    fn set_history_text(&mut self, widthdb: &mut WidthDb, text: String) {
+        // Like in shells, the cursor is placed at the end of the entry
+        self.text.set(text);
+        self.selection_anchor = None;
+        self.cursor_idx = self.text.len();
+        self.record_cursor_col(widthdb);
+    }
+
+    /// Whether the cursor is on the first line of the text.
+    pub(super) fn cursor_on_first_line(&self) -> bool {
+        self.cursor_line().0 == 0
+    }
+
+    /// Whether the cursor is on the last line of the text.
+    pub(super) fn cursor_on_last_line(&self) -> bool {
+        self.cursor_line().0 + 1 >= self.text.len_lines()
+    }
+}
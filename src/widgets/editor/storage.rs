@@ -0,0 +1,309 @@
+//! Storage for the text of an [`EditorState`](super::EditorState).
+//!
+//! With the `ropey` feature, the text is stored in a rope, which makes edits
+//! and line lookups in large texts cheap. The contiguous string returned by
+//! [`Storage::as_str`] is then only built when it is actually needed, so the
+//! editor itself only ever looks at individual lines or ranges of the text.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+#[cfg(feature = "ropey")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "ropey")]
+use ropey::Rope;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::WidthDb;
+
+use super::wrap_cache::{Row, WrapCache};
+
+#[cfg(not(feature = "ropey"))]
+#[derive(Debug, Clone, Default)]
+pub(super) struct Storage {
+    text: String,
+    graphemes: usize,
+    wrap_cache: WrapCache,
+    revision: u64,
+}
+
+#[cfg(not(feature = "ropey"))]
+impl Storage {
+    pub fn new(text: String) -> Self {
+        Self {
+            graphemes: text.graphemes(true).count(),
+            text,
+            wrap_cache: WrapCache::default(),
+            revision: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn insert_str(&mut self, idx: usize, str: &str) {
+        self.invalidate(idx..idx, str);
+        self.text.insert_str(idx, str);
+    }
+
+    pub fn replace_range(&mut self, range: Range<usize>, str: &str) {
+        self.invalidate(range.clone(), str);
+        self.text.replace_range(range, str);
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> Cow<'_, str> {
+        Cow::Borrowed(&self.text[range])
+    }
+
+    /// Like [`Self::slice`], but returns `None` if the range is out of bounds
+    /// or doesn't lie on char boundaries.
+    pub fn get(&self, range: Range<usize>) -> Option<Cow<'_, str>> {
+        self.text.get(range).map(Cow::Borrowed)
+    }
+
+    /// The closest char boundary at or before an index.
+    pub fn floor_char_boundary(&self, idx: usize) -> usize {
+        let idx = idx.min(self.text.len());
+        (0..=idx)
+            .rev()
+            .find(|i| self.text.is_char_boundary(*i))
+            .unwrap_or(0)
+    }
+
+    /// Amount of lines in the text.
+    ///
+    /// The last line can have a length of 0 if the text ends with a newline.
+    pub fn len_lines(&self) -> usize {
+        self.text.matches('\n').count() + 1
+    }
+
+    /// Find the line containing an index.
+    ///
+    /// Returns the line number and the range of the line, including its
+    /// trailing newline.
+    pub fn line_at(&self, idx: usize) -> (usize, Range<usize>) {
+        let before = &self.text[..idx];
+        let line = before.matches('\n').count();
+        let start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let end = self.text[idx..]
+            .find('\n')
+            .map(|i| idx + i + 1)
+            .unwrap_or(self.text.len());
+        (line, start..end)
+    }
+
+    /// The range of a line, including its trailing newline.
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        // utf-8 encodes '\n' as a single byte
+        let mut starts = self.text.match_indices('\n').map(|(i, _)| i + 1);
+        let start = match line {
+            0 => 0,
+            line => starts.nth(line - 1).expect("line exists"),
+        };
+        let end = starts.next().unwrap_or(self.text.len());
+        start..end
+    }
+
+    /// The ranges and contents of all lines, including their trailing
+    /// newlines.
+    pub fn lines(&self) -> impl Iterator<Item = (Range<usize>, Cow<'_, str>)> {
+        let starts = self.text.match_indices('\n').map(|(i, _)| i + 1);
+        std::iter::once(0).chain(starts).map(|start| {
+            let end = self.text[start..]
+                .find('\n')
+                .map(|i| start + i + 1)
+                .unwrap_or(self.text.len());
+            (start..end, Cow::Borrowed(&self.text[start..end]))
+        })
+    }
+}
+
+#[cfg(feature = "ropey")]
+#[derive(Debug, Clone, Default)]
+pub(super) struct Storage {
+    rope: Rope,
+    /// The contents of the rope as a contiguous string, built on demand.
+    cache: OnceLock<String>,
+    graphemes: usize,
+    wrap_cache: WrapCache,
+    revision: u64,
+}
+
+#[cfg(feature = "ropey")]
+impl Storage {
+    pub fn new(text: String) -> Self {
+        Self {
+            rope: Rope::from_str(&text),
+            graphemes: text.graphemes(true).count(),
+            cache: OnceLock::from(text),
+            wrap_cache: WrapCache::default(),
+            revision: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.cache.get_or_init(|| self.rope.to_string())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rope.len_bytes()
+    }
+
+    pub fn insert_str(&mut self, idx: usize, str: &str) {
+        self.invalidate(idx..idx, str);
+        self.cache = OnceLock::new();
+        let idx = self.rope.byte_to_char(idx);
+        self.rope.insert(idx, str);
+    }
+
+    pub fn replace_range(&mut self, range: Range<usize>, str: &str) {
+        self.invalidate(range.clone(), str);
+        self.cache = OnceLock::new();
+        let start = self.rope.byte_to_char(range.start);
+        let end = self.rope.byte_to_char(range.end);
+        self.rope.remove(start..end);
+        self.rope.insert(start, str);
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> Cow<'_, str> {
+        if let Some(text) = self.cache.get() {
+            return Cow::Borrowed(&text[range]);
+        }
+        self.rope.byte_slice(range).into()
+    }
+
+    /// Like [`Self::slice`], but returns `None` if the range is out of bounds
+    /// or doesn't lie on char boundaries.
+    pub fn get(&self, range: Range<usize>) -> Option<Cow<'_, str>> {
+        if let Some(text) = self.cache.get() {
+            return text.get(range).map(Cow::Borrowed);
+        }
+        self.rope.get_byte_slice(range).map(Cow::from)
+    }
+
+    /// The closest char boundary at or before an index.
+    pub fn floor_char_boundary(&self, idx: usize) -> usize {
+        let idx = idx.min(self.rope.len_bytes());
+        self.rope.char_to_byte(self.rope.byte_to_char(idx))
+    }
+
+    /// Amount of lines in the text.
+    ///
+    /// The last line can have a length of 0 if the text ends with a newline.
+    pub fn len_lines(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    /// Find the line containing an index.
+    ///
+    /// Returns the line number and the range of the line, including its
+    /// trailing newline.
+    pub fn line_at(&self, idx: usize) -> (usize, Range<usize>) {
+        let line = self.rope.byte_to_line(idx);
+        (line, self.line_range(line))
+    }
+
+    /// The range of a line, including its trailing newline.
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        let start = self.rope.line_to_byte(line);
+        let end = self.rope.line_to_byte(line + 1);
+        start..end
+    }
+
+    /// The ranges and contents of all lines, including their trailing
+    /// newlines.
+    pub fn lines(&self) -> impl Iterator<Item = (Range<usize>, Cow<'_, str>)> {
+        self.rope.lines().scan(0, |start, line| {
+            let range = *start..*start + line.len_bytes();
+            *start = range.end;
+            Some((range, Cow::from(line)))
+        })
+    }
+}
+
+impl Storage {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Amount of graphemes in the text.
+    pub fn graphemes(&self) -> usize {
+        self.graphemes
+    }
+
+    /// Replace the entire text.
+    pub fn set(&mut self, text: String) {
+        let revision = self.revision.wrapping_add(1);
+        *self = Self::new(text);
+        self.revision = revision;
+    }
+
+    /// A number that changes whenever the text is modified.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Invalidate cached information about the lines affected by replacing a
+    /// range of the text.
+    fn invalidate(&mut self, range: Range<usize>, str: &str) {
+        self.revision = self.revision.wrapping_add(1);
+        let (first, first_range) = self.line_at(range.start);
+        let (last, last_range) = self.line_at(range.end);
+
+        // Graphemes never span multiple lines, so only the affected lines
+        // need to be counted again.
+        let before = self.slice(first_range.start..last_range.end);
+        let prefix = self.slice(first_range.start..range.start);
+        let suffix = self.slice(range.end..last_range.end);
+        let after = format!("{prefix}{str}{suffix}");
+        self.graphemes =
+            self.graphemes - before.graphemes(true).count() + after.graphemes(true).count();
+
+        self.wrap_cache
+            .invalidate_lines(first, last - first, str.matches('\n').count());
+    }
+
+    /// Wrap each line of the text like [`WidthDb::wrap`].
+    ///
+    /// Lines that were not modified since the last call are not wrapped again.
+    pub fn wrap(&self, widthdb: &mut WidthDb, width: usize, indent: usize) -> Vec<Row> {
+        self.wrap_cache.wrap(self, widthdb, width, indent)
+    }
+
+    /// Ranges of all occurrences of a non-empty pattern, in order.
+    ///
+    /// Patterns without newlines are searched for line by line.
+    pub fn matches(&self, pattern: &str) -> Vec<Range<usize>> {
+        if pattern.contains('\n') {
+            return self
+                .as_str()
+                .match_indices(pattern)
+                .map(|(i, m)| i..i + m.len())
+                .collect();
+        }
+
+        let mut result = vec![];
+        for (range, text) in self.lines() {
+            result.extend(
+                text.match_indices(pattern)
+                    .map(|(i, m)| range.start + i..range.start + i + m.len()),
+            );
+        }
+        result
+    }
+}
+
+impl fmt::Display for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (_, line) in self.lines() {
+            f.write_str(&line)?;
+        }
+        Ok(())
+    }
+}
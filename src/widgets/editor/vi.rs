@@ -0,0 +1,286 @@
+//! Vi-style modal editing for [`EditorState`].
+//!
+//! Since the cursor of an [`EditorState`] is always located between two
+//! graphemes instead of on top of one, motions like `e` and `$` place the
+//! cursor after the last grapheme of the word or line.
+
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::WidthDb;
+
+use super::EditorState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViMode {
+    Normal,
+    Insert,
+    Visual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'd' => Some(Self::Delete),
+            'c' => Some(Self::Change),
+            'y' => Some(Self::Yank),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    LineEnd,
+}
+
+impl Motion {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'h' => Some(Self::Left),
+            'l' => Some(Self::Right),
+            'k' => Some(Self::Up),
+            'j' => Some(Self::Down),
+            'w' => Some(Self::WordForward),
+            'b' => Some(Self::WordBackward),
+            'e' => Some(Self::WordEnd),
+            '0' => Some(Self::LineStart),
+            '$' => Some(Self::LineEnd),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct ViState {
+    mode: ViMode,
+    /// Operator waiting for its motion.
+    pending: Option<Operator>,
+}
+
+impl EditorState {
+    /// The current vi mode, or `None` if vi mode is disabled.
+    pub fn vi_mode(&self) -> Option<ViMode> {
+        self.vi.as_ref().map(|vi| vi.mode)
+    }
+
+    /// Enable vi mode in the given mode, or disable it by passing `None`.
+    pub fn set_vi_mode(&mut self, mode: Option<ViMode>) {
+        if mode != Some(ViMode::Visual) {
+            self.selection_anchor = None;
+        } else if self.selection_anchor.is_none() {
+            self.selection_anchor = Some(self.cursor_idx);
+        }
+
+        self.vi = mode.map(|mode| ViState {
+            mode,
+            pending: None,
+        });
+    }
+
+    pub fn with_vi_mode(mut self, mode: Option<ViMode>) -> Self {
+        self.set_vi_mode(mode);
+        self
+    }
+
+    /// Handle a key event according to the current vi mode.
+    ///
+    /// Returns `true` if the key was handled. In normal and visual mode, all
+    /// keys producing characters are handled, even if they aren't bound to
+    /// anything. In insert mode, only escape is handled, so the key should be
+    /// processed like in a regular editor if this function returns `false`.
+    ///
+    /// Does nothing and returns `false` if vi mode is disabled.
+    pub fn handle_vi_key(&mut self, widthdb: &mut WidthDb, event: KeyEvent) -> bool {
+        let Some(mode) = self.vi_mode() else {
+            return false;
+        };
+
+        if event.code == KeyCode::Esc {
+            self.set_vi_mode(Some(ViMode::Normal));
+            return true;
+        }
+
+        let ch = match event.code {
+            KeyCode::Char(ch) if !event.modifiers.intersects(!KeyModifiers::SHIFT) => ch,
+            _ => return false,
+        };
+
+        match mode {
+            ViMode::Normal => self.handle_vi_normal_char(widthdb, ch),
+            ViMode::Insert => return false,
+            ViMode::Visual => self.handle_vi_visual_char(widthdb, ch),
+        }
+
+        true
+    }
+
+    fn handle_vi_normal_char(&mut self, widthdb: &mut WidthDb, ch: char) {
+        let pending = self.vi.as_mut().and_then(|vi| vi.pending.take());
+
+        if let Some(operator) = pending {
+            if Operator::from_char(ch) == Some(operator) {
+                let (_, start, end) = self.cursor_line();
+                self.apply_vi_operator(widthdb, operator, start..end);
+            } else if let Some(motion) = Motion::from_char(ch) {
+                // Like in vim, "cw" behaves like "ce"
+                let motion = match (operator, motion) {
+                    (Operator::Change, Motion::WordForward) => Motion::WordEnd,
+                    _ => motion,
+                };
+                let target = self.vi_motion_target(widthdb, motion);
+                let range = self.cursor_idx.min(target)..self.cursor_idx.max(target);
+                self.apply_vi_operator(widthdb, operator, range);
+            }
+            return;
+        }
+
+        if let Some(motion) = Motion::from_char(ch) {
+            self.move_cursor_vi(widthdb, motion);
+            return;
+        }
+
+        if let Some(operator) = Operator::from_char(ch) {
+            if let Some(vi) = &mut self.vi {
+                vi.pending = Some(operator);
+            }
+            return;
+        }
+
+        match ch {
+            'i' => self.set_vi_mode(Some(ViMode::Insert)),
+            'a' => {
+                self.move_cursor_right(widthdb);
+                self.set_vi_mode(Some(ViMode::Insert));
+            }
+            'I' => {
+                self.move_cursor_to_start_of_line(widthdb);
+                self.set_vi_mode(Some(ViMode::Insert));
+            }
+            'A' => {
+                self.move_cursor_to_end_of_line(widthdb);
+                self.set_vi_mode(Some(ViMode::Insert));
+            }
+            'o' => {
+                self.move_cursor_to_end_of_line(widthdb);
+                self.insert_newline(widthdb);
+                self.set_vi_mode(Some(ViMode::Insert));
+            }
+            'O' => {
+                self.move_cursor_to_start_of_line(widthdb);
+                self.insert_char(widthdb, '\n');
+                self.move_cursor_left(widthdb);
+                self.set_vi_mode(Some(ViMode::Insert));
+            }
+            'x' => self.delete(),
+            'X' => self.backspace(widthdb),
+            'p' => self.yank(widthdb),
+            'v' => self.set_vi_mode(Some(ViMode::Visual)),
+            _ => {}
+        }
+    }
+
+    fn handle_vi_visual_char(&mut self, widthdb: &mut WidthDb, ch: char) {
+        if let Some(motion) = Motion::from_char(ch) {
+            let anchor = self.selection_anchor.unwrap_or(self.cursor_idx);
+            self.move_cursor_vi(widthdb, motion);
+            self.selection_anchor = Some(anchor);
+            return;
+        }
+
+        let operator = match ch {
+            'x' => Some(Operator::Delete),
+            ch => Operator::from_char(ch),
+        };
+
+        if let Some(operator) = operator {
+            let range = self.selection().unwrap_or(self.cursor_idx..self.cursor_idx);
+            self.apply_vi_operator(widthdb, operator, range);
+        } else if ch == 'v' {
+            self.set_vi_mode(Some(ViMode::Normal));
+        }
+    }
+
+    fn vi_motion_target(&mut self, widthdb: &mut WidthDb, motion: Motion) -> usize {
+        let cursor_idx = self.cursor_idx;
+        let cursor_col = self.cursor_col;
+        self.move_cursor_vi(widthdb, motion);
+        let target = self.cursor_idx;
+        self.cursor_idx = cursor_idx;
+        self.cursor_col = cursor_col;
+        target
+    }
+
+    fn move_cursor_vi(&mut self, widthdb: &mut WidthDb, motion: Motion) {
+        match motion {
+            Motion::Left => self.move_cursor_left(widthdb),
+            Motion::Right => self.move_cursor_right(widthdb),
+            Motion::Up => self.move_cursor_up(widthdb),
+            Motion::Down => self.move_cursor_down(widthdb),
+            Motion::WordForward => {
+                self.selection_anchor = None;
+                self.cursor_idx = self.word_start_after_cursor();
+                self.record_cursor_col(widthdb);
+            }
+            Motion::WordBackward => self.move_cursor_left_a_word(widthdb),
+            Motion::WordEnd => self.move_cursor_right_a_word(widthdb),
+            Motion::LineStart => self.move_cursor_to_start_of_line(widthdb),
+            Motion::LineEnd => self.move_cursor_to_end_of_line(widthdb),
+        }
+    }
+
+    /// Find the start of the next word after the cursor.
+    fn word_start_after_cursor(&self) -> usize {
+        let mut encountered_whitespace = false;
+        let mut result = self.cursor_idx;
+        for (range, whitespace) in self.graphemes_after(self.cursor_idx) {
+            if encountered_whitespace && !whitespace {
+                break;
+            } else if whitespace {
+                encountered_whitespace = true;
+            }
+            result = range.end;
+        }
+        result
+    }
+
+    fn apply_vi_operator(
+        &mut self,
+        widthdb: &mut WidthDb,
+        operator: Operator,
+        range: Range<usize>,
+    ) {
+        match operator {
+            Operator::Delete => {
+                self.kill(widthdb, range);
+                self.set_vi_mode(Some(ViMode::Normal));
+            }
+            Operator::Change => {
+                self.kill(widthdb, range);
+                self.set_vi_mode(Some(ViMode::Insert));
+            }
+            Operator::Yank => {
+                self.push_kill(self.text.slice(range.clone()).into_owned());
+                self.cursor_idx = range.start;
+                self.record_cursor_col(widthdb);
+                self.set_vi_mode(Some(ViMode::Normal));
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Caching the wrapped lines of an [`EditorState`](super::EditorState).
+//!
+//! Since lines are always wrapped independently of each other, the rows are
+//! cached per line. Edits only invalidate the lines they touch.
+
+use std::ops::Range;
+use std::sync::{Mutex, PoisonError};
+
+use crate::WidthDb;
+
+use super::storage::Storage;
+
+/// A row of a wrapped line.
+#[derive(Debug, Clone)]
+pub(super) struct Row {
+    /// The line the row belongs to.
+    pub line: usize,
+    /// Range of the row within its line.
+    pub range: Range<usize>,
+    /// Width of the row.
+    pub width: usize,
+}
+
+/// Wrap a single line like [`WidthDb::wrap`].
+///
+/// Rows continuing a soft-wrapped line have `indent` fewer columns.
+pub(super) fn wrap_line(
+    widthdb: &mut WidthDb,
+    line: usize,
+    text: &str,
+    width: usize,
+    indent: usize,
+) -> Vec<Row> {
+    let breaks = widthdb.wrap_indented(text, width, 0, indent);
+    let starts = std::iter::once(0).chain(breaks.iter().copied());
+    let ends = breaks.iter().copied().chain(std::iter::once(text.len()));
+    starts
+        .zip(ends)
+        .map(|(start, end)| Row {
+            line,
+            range: start..end,
+            width: widthdb.width(&text[start..end]),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+struct Inner {
+    width: usize,
+    indent: usize,
+    generation: u64,
+    /// The rows of each line of the text, or empty if nothing is cached.
+    lines: Vec<Option<Vec<Row>>>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct WrapCache {
+    // A mutex instead of a RefCell so that the editor state stays Sync.
+    inner: Mutex<Inner>,
+}
+
+impl Clone for WrapCache {
+    fn clone(&self) -> Self {
+        let inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        Self {
+            inner: Mutex::new(inner.clone()),
+        }
+    }
+}
+
+impl WrapCache {
+    fn inner_mut(&mut self) -> &mut Inner {
+        self.inner.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Invalidate the lines affected by an edit.
+    ///
+    /// The edit started in line `first`, removed `removed` newlines and
+    /// inserted `inserted` newlines.
+    pub fn invalidate_lines(&mut self, first: usize, removed: usize, inserted: usize) {
+        let inner = self.inner_mut();
+        if first + removed >= inner.lines.len() {
+            inner.lines.clear();
+            return;
+        }
+        inner
+            .lines
+            .splice(first..=first + removed, (0..=inserted).map(|_| None));
+    }
+
+    /// Wrap each line of the text like [`wrap_line`].
+    pub fn wrap(
+        &self,
+        storage: &Storage,
+        widthdb: &mut WidthDb,
+        width: usize,
+        indent: usize,
+    ) -> Vec<Row> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let outdated = inner.width != width
+            || inner.indent != indent
+            || inner.generation != widthdb.generation();
+        if outdated {
+            inner.width = width;
+            inner.indent = indent;
+            inner.generation = widthdb.generation();
+            inner.lines.clear();
+        }
+
+        // Edits keep the cache in sync with the lines of the text, so this
+        // only happens if nothing was cached yet.
+        let len_lines = storage.len_lines();
+        if inner.lines.len() != len_lines {
+            inner.lines = vec![None; len_lines];
+        }
+
+        let mut rows = vec![];
+        for (i, ((_, text), line)) in storage.lines().zip(&mut inner.lines).enumerate() {
+            let line = line.get_or_insert_with(|| wrap_line(widthdb, i, &text, width, indent));
+            rows.extend(line.iter().cloned().map(|row| Row { line: i, ..row }));
+        }
+
+        rows
+    }
+}
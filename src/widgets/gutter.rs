@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+
+use crate::{AsyncWidget, Frame, Pos, Size, Style, Styled, Widget, WidthDb};
+
+/// Widgets whose rows correspond to (possibly soft-wrapped) lines of text.
+pub trait LineStarts {
+    /// For each row the widget would draw at the given width, whether the row
+    /// starts a new line or continues the previous one.
+    fn line_starts(&self, widthdb: &mut WidthDb, max_width: Option<u16>) -> Vec<bool>;
+}
+
+/// Determine for each row whether it starts a new line, assuming rows were
+/// split at newlines and soft line breaks.
+pub(crate) fn row_line_starts(rows: &[Styled]) -> Vec<bool> {
+    let mut result = Vec::with_capacity(rows.len());
+    let mut start = true;
+    for row in rows {
+        result.push(start);
+        start = row.text().ends_with('\n');
+    }
+    result
+}
+
+/// Displays line numbers to the left of its inner widget.
+///
+/// Rows continuing a soft-wrapped line display the continuation marker
+/// instead of a line number.
+#[derive(Debug, Clone)]
+pub struct Gutter<I> {
+    pub inner: I,
+    /// Number of the first line.
+    pub first_line: usize,
+    pub style: Style,
+    pub continuation: Styled,
+}
+
+impl<I> Gutter<I> {
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            first_line: 1,
+            style: Style::new(),
+            continuation: Styled::default(),
+        }
+    }
+
+    pub fn with_first_line(mut self, first_line: usize) -> Self {
+        self.first_line = first_line;
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_continuation<S: Into<Styled>>(mut self, continuation: S) -> Self {
+        self.continuation = continuation.into();
+        self
+    }
+}
+
+impl<I> Gutter<I> {
+    /// Width of the gutter for the given amount of lines, including a single
+    /// column of space between the line numbers and the inner widget.
+    pub(crate) fn width_for_lines(&self, widthdb: &mut WidthDb, lines: usize) -> u16 {
+        let last_line = self.first_line + lines.saturating_sub(1);
+
+        let digits = last_line.to_string().len();
+        let continuation = self.continuation.width(widthdb);
+        let width = digits.max(continuation) + 1;
+        width.try_into().unwrap_or(u16::MAX)
+    }
+
+    /// Draw the line numbers for the given rows, the first of which is drawn
+    /// at `first_y`.
+    pub(crate) fn draw_rows(
+        &self,
+        frame: &mut Frame,
+        gutter_width: u16,
+        starts: &[bool],
+        first_y: i32,
+    ) {
+        let number_width = gutter_width.saturating_sub(1) as usize;
+
+        let mut line = self.first_line;
+        for (i, start) in starts.iter().enumerate() {
+            let i: i32 = i.try_into().unwrap_or(i32::MAX);
+            let y = first_y.saturating_add(i);
+            if *start {
+                let number = format!("{line:>number_width$}");
+                frame.write(Pos::new(0, y), (number, self.style));
+                line += 1;
+            } else {
+                frame.write(Pos::new(0, y), self.continuation.clone());
+            }
+        }
+    }
+}
+
+impl<I: LineStarts> Gutter<I> {
+    fn gutter_width(&self, widthdb: &mut WidthDb) -> u16 {
+        // The amount of lines does not depend on the width.
+        let lines = self
+            .inner
+            .line_starts(widthdb, None)
+            .into_iter()
+            .filter(|s| *s)
+            .count();
+        self.width_for_lines(widthdb, lines)
+    }
+
+    fn draw_gutter(&self, frame: &mut Frame, gutter_width: u16) {
+        let inner_width = frame.size().width.saturating_sub(gutter_width);
+        let starts = self.inner.line_starts(frame.widthdb(), Some(inner_width));
+        self.draw_rows(frame, gutter_width, &starts, 0);
+    }
+
+    fn push_inner(&self, frame: &mut Frame, gutter_width: u16) {
+        let mut size = frame.size();
+        size.width = size.width.saturating_sub(gutter_width);
+        frame.push(Pos::new(gutter_width.into(), 0), size);
+    }
+}
+
+impl<E, I> Widget<E> for Gutter<I>
+where
+    I: Widget<E> + LineStarts,
+{
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let gutter_width = self.gutter_width(widthdb);
+        let max_width = max_width.map(|w| w.saturating_sub(gutter_width));
+        let size = self.inner.size(widthdb, max_width, max_height)?;
+        Ok(size + Size::new(gutter_width, 0))
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let gutter_width = self.gutter_width(frame.widthdb());
+        self.draw_gutter(frame, gutter_width);
+
+        self.push_inner(frame, gutter_width);
+        self.inner.draw(frame)?;
+        frame.pop();
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, I> AsyncWidget<E> for Gutter<I>
+where
+    I: AsyncWidget<E> + LineStarts + Send + Sync,
+{
+    async fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let gutter_width = self.gutter_width(widthdb);
+        let max_width = max_width.map(|w| w.saturating_sub(gutter_width));
+        let size = self.inner.size(widthdb, max_width, max_height).await?;
+        Ok(size + Size::new(gutter_width, 0))
+    }
+
+    async fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let gutter_width = self.gutter_width(frame.widthdb());
+        self.draw_gutter(frame, gutter_width);
+
+        self.push_inner(frame, gutter_width);
+        self.inner.draw(frame).await?;
+        frame.pop();
+
+        Ok(())
+    }
+}
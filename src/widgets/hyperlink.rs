@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+
+use crate::{AsyncWidget, Frame, Size, Widget, WidthDb};
+
+/// Turns all text drawn by the inner widget into a hyperlink.
+///
+/// Whether the hyperlink is clickable depends on the terminal. For more
+/// details, see [`crate::Terminal::hyperlinks`].
+#[derive(Debug, Clone)]
+pub struct Hyperlink<I> {
+    pub inner: I,
+    pub url: String,
+}
+
+impl<I> Hyperlink<I> {
+    pub fn new<S: ToString>(inner: I, url: S) -> Self {
+        Self {
+            inner,
+            url: url.to_string(),
+        }
+    }
+}
+
+impl<E, I> Widget<E> for Hyperlink<I>
+where
+    I: Widget<E>,
+{
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        self.inner.size(widthdb, max_width, max_height)
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        frame.push_link(&self.url);
+        self.inner.draw(frame)?;
+        frame.pop_link();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, I> AsyncWidget<E> for Hyperlink<I>
+where
+    I: AsyncWidget<E> + Send + Sync,
+{
+    async fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        self.inner.size(widthdb, max_width, max_height).await
+    }
+
+    async fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        frame.push_link(&self.url);
+        self.inner.draw(frame).await?;
+        frame.pop_link();
+        Ok(())
+    }
+}
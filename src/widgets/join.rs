@@ -398,7 +398,7 @@ where
             balance(&mut segments, available);
 
             let mut new_segments = Vec::with_capacity(self.segments.len());
-            for (segment, balanced) in self.segments.iter().zip(segments.into_iter()) {
+            for (segment, balanced) in self.segments.iter().zip(segments) {
                 let major_minor =
                     size_with_balanced(self.horizontal, widthdb, segment, &balanced, max_minor)?;
                 new_segments.push(Segment::new(major_minor, segment));
@@ -424,7 +424,7 @@ where
         balance(&mut segments, max_major);
 
         let mut major = 0_i32;
-        for (segment, balanced) in self.segments.into_iter().zip(segments.into_iter()) {
+        for (segment, balanced) in self.segments.into_iter().zip(segments) {
             let (x, y) = from_mm(self.horizontal, major, 0);
             let (w, h) = from_mm(self.horizontal, balanced.major, max_minor);
             frame.push(Pos::new(x, y), Size::new(w, h));
@@ -461,7 +461,7 @@ where
             balance(&mut segments, available);
 
             let mut new_segments = Vec::with_capacity(self.segments.len());
-            for (segment, balanced) in self.segments.iter().zip(segments.into_iter()) {
+            for (segment, balanced) in self.segments.iter().zip(segments) {
                 let major_minor = size_async_with_balanced(
                     self.horizontal,
                     widthdb,
@@ -494,7 +494,7 @@ where
         balance(&mut segments, max_major);
 
         let mut major = 0_i32;
-        for (segment, balanced) in self.segments.into_iter().zip(segments.into_iter()) {
+        for (segment, balanced) in self.segments.into_iter().zip(segments) {
             let (x, y) = from_mm(self.horizontal, major, 0);
             let (w, h) = from_mm(self.horizontal, balanced.major, max_minor);
             frame.push(Pos::new(x, y), Size::new(w, h));
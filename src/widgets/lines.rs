@@ -0,0 +1,105 @@
+use crate::{Frame, Pos, Size, Styled, Widget, WidthDb};
+
+/// Displays each [`Styled`] in its own row without any wrapping.
+///
+/// Unlike [`super::Text`], the lines are never reflowed, so they should not
+/// contain any newlines. Lines that are too wide are cut off.
+#[derive(Debug, Clone)]
+pub struct Lines {
+    pub lines: Vec<Styled>,
+    vertical: f32,
+    /// Whether lines that are too wide should end in an ellipsis.
+    pub ellipsis: bool,
+}
+
+impl Lines {
+    pub fn new(lines: Vec<Styled>) -> Self {
+        Self {
+            lines,
+            vertical: 0.0,
+            ellipsis: false,
+        }
+    }
+
+    pub fn vertical(&self) -> f32 {
+        self.vertical
+    }
+
+    /// Set the vertical position of the lines within the available space.
+    ///
+    /// A position of 0.0 aligns the lines with the top, 1.0 with the bottom. If
+    /// there are more lines than available rows, the position also determines
+    /// which lines are cut off, so 1.0 always shows the last line.
+    pub fn set_vertical(&mut self, position: f32) {
+        assert!((0.0..=1.0).contains(&position));
+        self.vertical = position;
+    }
+
+    pub fn with_vertical(mut self, position: f32) -> Self {
+        self.set_vertical(position);
+        self
+    }
+
+    pub fn with_top(self) -> Self {
+        self.with_vertical(0.0)
+    }
+
+    pub fn with_bottom(self) -> Self {
+        self.with_vertical(1.0)
+    }
+
+    pub fn with_center_v(self) -> Self {
+        self.with_vertical(0.5)
+    }
+
+    pub fn with_ellipsis(mut self, active: bool) -> Self {
+        self.ellipsis = active;
+        self
+    }
+}
+
+impl<E> Widget<E> for Lines {
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        _max_width: Option<u16>,
+        _max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let width = self
+            .lines
+            .iter()
+            .map(|l| l.width(widthdb))
+            .max()
+            .unwrap_or(0);
+        let height = self.lines.len();
+
+        let width: u16 = width.try_into().unwrap_or(u16::MAX);
+        let height: u16 = height.try_into().unwrap_or(u16::MAX);
+        Ok(Size::new(width, height))
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let size = frame.size();
+
+        // Biased towards the top if vertical lands exactly on the boundary
+        // between two cells
+        let available = size.height as f32 - self.lines.len() as f32;
+        let top = (self.vertical * available).floor() as i32;
+
+        for (i, line) in self.lines.into_iter().enumerate() {
+            let y = top.saturating_add(i.try_into().unwrap_or(i32::MAX));
+            if y < 0 || y >= size.height.into() {
+                continue;
+            }
+
+            let too_wide = self.ellipsis && line.width(frame.widthdb()) > size.width.into();
+            frame.write(Pos::new(0, y), line);
+            if too_wide {
+                let x = size.width.saturating_sub(1).into();
+                frame.write(Pos::new(x, y), "…");
+            }
+        }
+
+        Ok(())
+    }
+}
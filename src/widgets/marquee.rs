@@ -0,0 +1,68 @@
+use crate::{Frame, Pos, Size, Styled, Widget, WidthDb};
+
+/// A single line of text that scrolls horizontally if it is too wide.
+///
+/// The text does not scroll by itself. Instead, the offset should be
+/// incremented by one for each new animation frame. Wide graphemes that are
+/// only partially visible at the edges are replaced by blank cells.
+#[derive(Debug, Clone)]
+pub struct Marquee {
+    pub styled: Styled,
+    /// How many columns the text has scrolled to the left.
+    pub offset: usize,
+    /// Amount of columns between the end of the text and its next repetition.
+    pub gap: u16,
+}
+
+impl Marquee {
+    pub fn new<S: Into<Styled>>(styled: S) -> Self {
+        Self {
+            styled: styled.into(),
+            offset: 0,
+            gap: 3,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+}
+
+impl<E> Widget<E> for Marquee {
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        _max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let width = self.styled.width(widthdb);
+        let width: u16 = width.try_into().unwrap_or(u16::MAX);
+        let width = match max_width {
+            Some(max_width) => width.min(max_width),
+            None => width,
+        };
+        Ok(Size::new(width, 1))
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let available = frame.size().width as usize;
+        let width = self.styled.width(frame.widthdb());
+        if width <= available {
+            frame.write(Pos::ZERO, self.styled);
+            return Ok(());
+        }
+
+        let period = width + self.gap as usize;
+        let start = (self.offset % period) as i32;
+        frame.write(Pos::new(-start, 0), self.styled.clone());
+        frame.write(Pos::new(period as i32 - start, 0), self.styled);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use async_trait::async_trait;
+
+use crate::{AsyncWidget, Frame, Pos, Size, Widget, WidthDb};
+
+/// Distributes its children across multiple columns of equal width.
+///
+/// Children are placed in order. Each child is appended to the column that is
+/// currently the shortest, or to the leftmost one if there are multiple.
+#[derive(Debug, Clone)]
+pub struct Masonry<I> {
+    columns: u16,
+    pub column_gap: u16,
+    pub row_gap: u16,
+    children: Vec<I>,
+}
+
+impl<I> Masonry<I> {
+    pub fn new(columns: u16, children: Vec<I>) -> Self {
+        assert!(columns > 0);
+        Self {
+            columns,
+            column_gap: 0,
+            row_gap: 0,
+            children,
+        }
+    }
+
+    pub fn columns(&self) -> u16 {
+        self.columns
+    }
+
+    pub fn set_columns(&mut self, columns: u16) {
+        assert!(columns > 0);
+        self.columns = columns;
+    }
+
+    pub fn with_columns(mut self, columns: u16) -> Self {
+        self.set_columns(columns);
+        self
+    }
+
+    pub fn with_column_gap(mut self, gap: u16) -> Self {
+        self.column_gap = gap;
+        self
+    }
+
+    pub fn with_row_gap(mut self, gap: u16) -> Self {
+        self.row_gap = gap;
+        self
+    }
+
+    pub fn with_gap(self, gap: u16) -> Self {
+        self.with_column_gap(gap).with_row_gap(gap)
+    }
+
+    fn total_gap(&self) -> u16 {
+        self.column_gap.saturating_mul(self.columns - 1)
+    }
+
+    /// Width of a column if all columns together should be at most `width`
+    /// wide.
+    ///
+    /// All columns have the same width, so some space may be left over on the
+    /// right.
+    fn column_width(&self, width: u16) -> u16 {
+        width.saturating_sub(self.total_gap()) / self.columns
+    }
+
+    fn column_x(&self, column_width: u16, column: usize) -> i32 {
+        (column_width as i32 + self.column_gap as i32) * column as i32
+    }
+
+    /// Assign each child to a column based on the heights of all children.
+    ///
+    /// Returns the column and vertical position of each child as well as the
+    /// height of the tallest column.
+    fn place(&self, heights: &[u16]) -> (Vec<(usize, i32)>, u16) {
+        let mut column_heights = vec![0_u16; self.columns.into()];
+        let mut result = Vec::with_capacity(heights.len());
+
+        for height in heights {
+            let (column, column_height) = column_heights
+                .iter_mut()
+                .enumerate()
+                .min_by_key(|(_, h)| **h)
+                .expect("at least one column");
+
+            if *column_height > 0 {
+                *column_height = column_height.saturating_add(self.row_gap);
+            }
+            result.push((column, *column_height as i32));
+            *column_height = column_height.saturating_add(*height);
+        }
+
+        let max_height = column_heights.into_iter().max().unwrap_or(0);
+        (result, max_height)
+    }
+
+    fn total_width(&self, column_width: u16) -> u16 {
+        column_width
+            .saturating_mul(self.columns)
+            .saturating_add(self.total_gap())
+    }
+}
+
+impl<E, I> Widget<E> for Masonry<I>
+where
+    I: Widget<E>,
+{
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        _max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let column_width = match max_width {
+            Some(width) => self.column_width(width),
+            None => {
+                let mut column_width = 0;
+                for child in &self.children {
+                    column_width = column_width.max(child.size(widthdb, None, None)?.width);
+                }
+                column_width
+            }
+        };
+
+        let mut heights = Vec::with_capacity(self.children.len());
+        for child in &self.children {
+            heights.push(child.size(widthdb, Some(column_width), None)?.height);
+        }
+        let (_, height) = self.place(&heights);
+
+        Ok(Size::new(self.total_width(column_width), height))
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let column_width = self.column_width(frame.size().width);
+
+        let mut heights = Vec::with_capacity(self.children.len());
+        for child in &self.children {
+            let size = child.size(frame.widthdb(), Some(column_width), None)?;
+            heights.push(size.height);
+        }
+        let (placements, _) = self.place(&heights);
+        let positions = placements
+            .into_iter()
+            .map(|(column, y)| Pos::new(self.column_x(column_width, column), y))
+            .collect::<Vec<_>>();
+
+        for ((child, height), pos) in self.children.into_iter().zip(heights).zip(positions) {
+            frame.push(pos, Size::new(column_width, height));
+            child.draw(frame)?;
+            frame.pop();
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, I> AsyncWidget<E> for Masonry<I>
+where
+    I: AsyncWidget<E> + Send + Sync,
+{
+    async fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        _max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let column_width = match max_width {
+            Some(width) => self.column_width(width),
+            None => {
+                let mut column_width = 0;
+                for child in &self.children {
+                    column_width = column_width.max(child.size(widthdb, None, None).await?.width);
+                }
+                column_width
+            }
+        };
+
+        let mut heights = Vec::with_capacity(self.children.len());
+        for child in &self.children {
+            heights.push(child.size(widthdb, Some(column_width), None).await?.height);
+        }
+        let (_, height) = self.place(&heights);
+
+        Ok(Size::new(self.total_width(column_width), height))
+    }
+
+    async fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let column_width = self.column_width(frame.size().width);
+
+        let mut heights = Vec::with_capacity(self.children.len());
+        for child in &self.children {
+            let size = child
+                .size(frame.widthdb(), Some(column_width), None)
+                .await?;
+            heights.push(size.height);
+        }
+        let (placements, _) = self.place(&heights);
+        let positions = placements
+            .into_iter()
+            .map(|(column, y)| Pos::new(self.column_x(column_width, column), y))
+            .collect::<Vec<_>>();
+
+        for ((child, height), pos) in self.children.into_iter().zip(heights).zip(positions) {
+            frame.push(pos, Size::new(column_width, height));
+            child.draw(frame).await?;
+            frame.pop();
+        }
+
+        Ok(())
+    }
+}
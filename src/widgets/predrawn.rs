@@ -60,9 +60,15 @@ impl<E> Widget<E> for Predrawn {
             let pos = Pos::new(x.into(), y.into());
             let style = Style {
                 content_style: cell.style,
-                opaque: true,
+                ..Style::new().opaque()
             };
-            frame.write(pos, Styled::new(&cell.content, style));
+            if let Some(link) = &cell.link {
+                frame.push_link(link);
+                frame.write(pos, Styled::new(&cell.content, style));
+                frame.pop_link();
+            } else {
+                frame.write(pos, Styled::new(&cell.content, style));
+            }
         }
 
         if let Some(cursor) = self.buffer.cursor() {
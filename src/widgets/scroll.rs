@@ -0,0 +1,405 @@
+use async_trait::async_trait;
+
+use crate::{AsyncWidget, Frame, Pos, Size, Widget, WidthDb};
+
+///////////
+// State //
+///////////
+
+/// Part of the content that should be scrolled into view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScrollTarget {
+    Rows { start: u16, height: u16 },
+    Section(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScrollState {
+    /// Amount of rows the content is scrolled down by.
+    ///
+    /// May be larger than the content allows. It is clamped whenever the
+    /// widget is drawn.
+    offset: u16,
+
+    /// Height of the content when the widget was last rendered.
+    last_content_height: u16,
+
+    /// Height of the viewport when the widget was last rendered.
+    last_viewport_height: u16,
+
+    /// Part of the content that should be scrolled into view the next time
+    /// the widget is rendered.
+    target: Option<ScrollTarget>,
+}
+
+impl ScrollState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn max_offset(&self) -> u16 {
+        self.last_content_height
+            .saturating_sub(self.last_viewport_height)
+    }
+
+    /// Update the offset based on the current content and viewport height.
+    ///
+    /// Must be called exactly once whenever the widget is drawn. Any
+    /// [`ScrollTarget::Section`] must already have been resolved.
+    fn update(&mut self, content_height: u16, viewport_height: u16) {
+        self.last_content_height = content_height;
+        self.last_viewport_height = viewport_height;
+
+        if let Some(ScrollTarget::Rows { start, height }) = self.target.take() {
+            let end = start.saturating_add(height);
+            if start < self.offset {
+                self.offset = start;
+            } else if end > self.offset.saturating_add(viewport_height) {
+                // If the rows don't fit into the viewport, their start is
+                // more important than their end.
+                self.offset = end.saturating_sub(viewport_height).min(start);
+            }
+        }
+
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    pub fn offset(&self) -> u16 {
+        self.offset
+    }
+
+    pub fn set_offset(&mut self, offset: u16) {
+        self.offset = offset;
+    }
+
+    /// Scroll down by `delta` rows, or up if `delta` is negative.
+    ///
+    /// Uses the content and viewport height from when the widget was last
+    /// rendered to prevent scrolling past the end of the content.
+    pub fn scroll_by(&mut self, delta: i32) {
+        let offset = self.offset.min(self.max_offset()) as i32 + delta;
+        self.offset = offset.clamp(0, self.max_offset().into()) as u16;
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.offset = 0;
+    }
+
+    /// Scroll to the bottom of the content.
+    ///
+    /// The bottom is determined the next time the widget is rendered.
+    pub fn scroll_to_bottom(&mut self) {
+        self.offset = u16::MAX;
+    }
+
+    /// Scroll the rows `start..start + height` of the content into view the
+    /// next time the widget is rendered.
+    ///
+    /// The offset is changed as little as possible. If the rows don't fit into
+    /// the viewport, the first row is shown at the top of the viewport.
+    pub fn ensure_visible(&mut self, start: u16, height: u16) {
+        self.target = Some(ScrollTarget::Rows { start, height });
+    }
+
+    /// Scroll a single row of the content into view the next time the widget
+    /// is rendered.
+    ///
+    /// For more details, see [`Self::ensure_visible`].
+    pub fn ensure_row_visible(&mut self, row: u16) {
+        self.ensure_visible(row, 1);
+    }
+
+    /// Scroll the section with the given index into view the next time a
+    /// [`StickyScroll`] is rendered.
+    ///
+    /// For more details, see [`Self::ensure_visible`].
+    pub fn ensure_section_visible(&mut self, index: usize) {
+        self.target = Some(ScrollTarget::Section(index));
+    }
+
+    pub fn last_content_height(&self) -> u16 {
+        self.last_content_height
+    }
+
+    pub fn last_viewport_height(&self) -> u16 {
+        self.last_viewport_height
+    }
+
+    pub fn widget<I>(&mut self, inner: I) -> Scroll<'_, I> {
+        Scroll { state: self, inner }
+    }
+
+    pub fn sticky_widget<H, I>(
+        &mut self,
+        sections: Vec<StickySection<H, I>>,
+    ) -> StickyScroll<'_, H, I> {
+        StickyScroll {
+            state: self,
+            sections,
+        }
+    }
+}
+
+////////////
+// Scroll //
+////////////
+
+/// Vertically scrollable view of a widget that may be higher than the
+/// available space.
+#[derive(Debug)]
+pub struct Scroll<'a, I> {
+    state: &'a mut ScrollState,
+    pub inner: I,
+}
+
+impl<I> Scroll<'_, I> {
+    pub fn state(&mut self) -> &mut ScrollState {
+        self.state
+    }
+
+    fn push_inner(&mut self, frame: &mut Frame, inner_height: u16) {
+        let size = frame.size();
+        if let Some(ScrollTarget::Section(_)) = self.state.target {
+            self.state.target = None; // There are no sections
+        }
+        self.state.update(inner_height, size.height);
+        let offset: i32 = self.state.offset.into();
+        frame.push(Pos::new(0, -offset), Size::new(size.width, inner_height));
+    }
+}
+
+impl<E, I> Widget<E> for Scroll<'_, I>
+where
+    I: Widget<E>,
+{
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let mut size = self.inner.size(widthdb, max_width, None)?;
+        if let Some(max_height) = max_height {
+            size.height = size.height.min(max_height);
+        }
+        Ok(size)
+    }
+
+    fn draw(mut self, frame: &mut Frame) -> Result<(), E> {
+        let width = frame.size().width;
+        let inner_size = self.inner.size(frame.widthdb(), Some(width), None)?;
+
+        self.push_inner(frame, inner_size.height);
+        self.inner.draw(frame)?;
+        frame.pop();
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, I> AsyncWidget<E> for Scroll<'_, I>
+where
+    I: AsyncWidget<E> + Send + Sync,
+{
+    async fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let mut size = self.inner.size(widthdb, max_width, None).await?;
+        if let Some(max_height) = max_height {
+            size.height = size.height.min(max_height);
+        }
+        Ok(size)
+    }
+
+    async fn draw(mut self, frame: &mut Frame) -> Result<(), E> {
+        let width = frame.size().width;
+        let inner_size = self.inner.size(frame.widthdb(), Some(width), None).await?;
+
+        self.push_inner(frame, inner_size.height);
+        self.inner.draw(frame).await?;
+        frame.pop();
+
+        Ok(())
+    }
+}
+
+//////////////////
+// StickyScroll //
+//////////////////
+
+/// A header followed by a body, used in a [`StickyScroll`].
+#[derive(Debug, Clone, Copy)]
+pub struct StickySection<H, I> {
+    pub header: H,
+    pub body: I,
+}
+
+impl<H, I> StickySection<H, I> {
+    pub fn new(header: H, body: I) -> Self {
+        Self { header, body }
+    }
+}
+
+/// Like [`Scroll`], but made up of multiple sections whose headers stick to
+/// the top of the viewport while their body is being scrolled through.
+///
+/// A stuck header is pushed up by the header of the following section once
+/// that header reaches it. Since headers are drawn on top of their body, they
+/// should usually have a [`super::Background`].
+#[derive(Debug)]
+pub struct StickyScroll<'a, H, I> {
+    state: &'a mut ScrollState,
+    pub sections: Vec<StickySection<H, I>>,
+}
+
+impl<H, I> StickyScroll<'_, H, I> {
+    pub fn state(&mut self) -> &mut ScrollState {
+        self.state
+    }
+}
+
+fn update_sticky_state(state: &mut ScrollState, heights: &[(u16, u16)], viewport_height: u16) {
+    let mut total = 0_u16;
+    for (i, (header, body)) in heights.iter().enumerate() {
+        let height = header.saturating_add(*body);
+        if state.target == Some(ScrollTarget::Section(i)) {
+            state.ensure_visible(total, height);
+        }
+        total = total.saturating_add(height);
+    }
+
+    if let Some(ScrollTarget::Section(_)) = state.target {
+        state.target = None; // Section index out of range
+    }
+    state.update(total, viewport_height);
+}
+
+/// Position of a section's header relative to the top of the viewport, given
+/// the position of the section itself.
+fn sticky_header_y(section_y: i32, header_height: u16, body_height: u16) -> i32 {
+    let section_end = section_y + header_height as i32 + body_height as i32;
+    section_y.max(0).min(section_end - header_height as i32)
+}
+
+impl<E, H, I> Widget<E> for StickyScroll<'_, H, I>
+where
+    H: Widget<E>,
+    I: Widget<E>,
+{
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let mut size = Size::ZERO;
+        for section in &self.sections {
+            let header = section.header.size(widthdb, max_width, None)?;
+            let body = section.body.size(widthdb, max_width, None)?;
+            size.width = size.width.max(header.width).max(body.width);
+            size.height = size
+                .height
+                .saturating_add(header.height)
+                .saturating_add(body.height);
+        }
+        if let Some(max_height) = max_height {
+            size.height = size.height.min(max_height);
+        }
+        Ok(size)
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let size = frame.size();
+
+        let mut heights = Vec::with_capacity(self.sections.len());
+        for section in &self.sections {
+            let widthdb = frame.widthdb();
+            let header = section.header.size(widthdb, Some(size.width), None)?;
+            let body = section.body.size(widthdb, Some(size.width), None)?;
+            heights.push((header.height, body.height));
+        }
+
+        update_sticky_state(self.state, &heights, size.height);
+
+        let mut y = -(self.state.offset as i32);
+        for (section, (header_height, body_height)) in self.sections.into_iter().zip(heights) {
+            let body_y = y + header_height as i32;
+            frame.push(Pos::new(0, body_y), Size::new(size.width, body_height));
+            section.body.draw(frame)?;
+            frame.pop();
+
+            let header_y = sticky_header_y(y, header_height, body_height);
+            frame.push(Pos::new(0, header_y), Size::new(size.width, header_height));
+            section.header.draw(frame)?;
+            frame.pop();
+
+            y = body_y + body_height as i32;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, H, I> AsyncWidget<E> for StickyScroll<'_, H, I>
+where
+    H: AsyncWidget<E> + Send + Sync,
+    I: AsyncWidget<E> + Send + Sync,
+{
+    async fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let mut size = Size::ZERO;
+        for section in &self.sections {
+            let header = section.header.size(widthdb, max_width, None).await?;
+            let body = section.body.size(widthdb, max_width, None).await?;
+            size.width = size.width.max(header.width).max(body.width);
+            size.height = size
+                .height
+                .saturating_add(header.height)
+                .saturating_add(body.height);
+        }
+        if let Some(max_height) = max_height {
+            size.height = size.height.min(max_height);
+        }
+        Ok(size)
+    }
+
+    async fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let size = frame.size();
+
+        let mut heights = Vec::with_capacity(self.sections.len());
+        for section in &self.sections {
+            let widthdb = frame.widthdb();
+            let header = section.header.size(widthdb, Some(size.width), None).await?;
+            let body = section.body.size(widthdb, Some(size.width), None).await?;
+            heights.push((header.height, body.height));
+        }
+
+        update_sticky_state(self.state, &heights, size.height);
+
+        let mut y = -(self.state.offset as i32);
+        for (section, (header_height, body_height)) in self.sections.into_iter().zip(heights) {
+            let body_y = y + header_height as i32;
+            frame.push(Pos::new(0, body_y), Size::new(size.width, body_height));
+            section.body.draw(frame).await?;
+            frame.pop();
+
+            let header_y = sticky_header_y(y, header_height, body_height);
+            frame.push(Pos::new(0, header_y), Size::new(size.width, header_height));
+            section.header.draw(frame).await?;
+            frame.pop();
+
+            y = body_y + body_height as i32;
+        }
+
+        Ok(())
+    }
+}
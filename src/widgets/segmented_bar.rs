@@ -0,0 +1,148 @@
+use crate::{Frame, Pos, Size, Style, Styled, TruncateAt, Widget, WidthDb};
+
+#[derive(Debug, Clone)]
+pub struct BarSegment {
+    pub value: f32,
+    pub label: Styled,
+    pub style: Style,
+}
+
+impl BarSegment {
+    pub fn new<S: Into<Styled>>(value: f32, label: S, style: Style) -> Self {
+        assert!(value >= 0.0);
+        Self {
+            value,
+            label: label.into(),
+            style,
+        }
+    }
+}
+
+/// A single-row bar divided into multiple segments, each taking up space
+/// proportional to its value.
+///
+/// Each segment displays its label if there is enough space. Labels that are
+/// too wide are shortened with an ellipsis or left out entirely.
+#[derive(Debug, Clone)]
+pub struct SegmentedBar {
+    pub segments: Vec<BarSegment>,
+    /// The value corresponding to the entire width of the bar.
+    ///
+    /// If this is `None`, the sum of all segment values is used instead.
+    pub total: Option<f32>,
+    /// Style of the part of the bar not covered by any segment.
+    pub style: Style,
+}
+
+impl SegmentedBar {
+    pub fn new(segments: Vec<BarSegment>) -> Self {
+        Self {
+            segments,
+            total: None,
+            style: Style::new(),
+        }
+    }
+
+    pub fn with_total(mut self, total: f32) -> Self {
+        assert!(total >= 0.0);
+        self.total = Some(total);
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Width of each segment in cells.
+    ///
+    /// Cells are assigned via the largest remainder method, so the widths
+    /// always add up to the exact width the values would indicate.
+    fn widths(&self, width: u16) -> Vec<u16> {
+        let sum = self.segments.iter().map(|s| s.value).sum::<f32>();
+        let total = self.total.unwrap_or(sum).max(sum);
+        if total <= 0.0 {
+            return vec![0; self.segments.len()];
+        }
+
+        let exact = self
+            .segments
+            .iter()
+            .map(|s| s.value / total * width as f32)
+            .collect::<Vec<_>>();
+        let mut widths = exact.iter().map(|w| w.floor() as u16).collect::<Vec<_>>();
+
+        let target = (sum / total * width as f32).round() as u16;
+        let used = widths.iter().sum::<u16>();
+        let mut by_remainder = (0..widths.len()).collect::<Vec<_>>();
+        by_remainder.sort_by(|a, b| {
+            let ra = exact[*a] - exact[*a].floor();
+            let rb = exact[*b] - exact[*b].floor();
+            rb.total_cmp(&ra)
+        });
+        for i in by_remainder
+            .into_iter()
+            .take(target.saturating_sub(used).into())
+        {
+            widths[i] += 1;
+        }
+
+        widths
+    }
+
+    /// Shorten a label so it fits into the given width.
+    fn elide(widthdb: &mut WidthDb, label: &Styled, width: usize) -> Styled {
+        if label.width(widthdb) <= width {
+            return label.clone();
+        }
+        if width < 2 {
+            return Styled::default();
+        }
+        label
+            .clone()
+            .truncate_to_width(widthdb, width, TruncateAt::End)
+    }
+}
+
+impl<E> Widget<E> for SegmentedBar {
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        _max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        let width = match max_width {
+            Some(width) => width,
+            None => {
+                let width = self
+                    .segments
+                    .iter()
+                    .map(|s| s.label.width(widthdb))
+                    .sum::<usize>();
+                width.try_into().unwrap_or(u16::MAX)
+            }
+        };
+        Ok(Size::new(width, 1))
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        let size = frame.size();
+        let widths = self.widths(size.width);
+
+        let background = " ".repeat(size.width.into());
+        frame.write(Pos::ZERO, (background, self.style));
+
+        let mut x = 0_i32;
+        for (segment, width) in self.segments.into_iter().zip(widths) {
+            let fill = " ".repeat(width.into());
+            frame.write(Pos::new(x, 0), (fill, segment.style));
+
+            let label = Self::elide(frame.widthdb(), &segment.label, width.into());
+            frame.write(Pos::new(x, 0), label);
+
+            x += width as i32;
+        }
+
+        Ok(())
+    }
+}
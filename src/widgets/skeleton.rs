@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use crossterm::style::Stylize;
+
+use crate::{AsyncWidget, Frame, Pos, Size, Style, Theme, Widget, WidthDb};
+
+/// Lengths of consecutive placeholder lines in percent of the available width.
+const LINE_LENGTHS: [u16; 4] = [100, 90, 95, 80];
+
+/// Length of the last placeholder line in percent of the available width.
+const LAST_LINE_LENGTH: u16 = 60;
+
+/// Width of the shimmer band in cells.
+const SHIMMER_WIDTH: i32 = 4;
+
+/// Placeholder for content that is still loading.
+///
+/// Instead of its inner widget, it draws lines of blocks that take up the same
+/// space as the inner widget would. A lighter band moves across the lines
+/// diagonally whenever the phase is advanced.
+#[derive(Debug, Clone, Copy)]
+pub struct Skeleton<I> {
+    pub inner: I,
+    /// Animation phase of the shimmer band.
+    ///
+    /// Should be incremented by one for each new animation frame.
+    pub phase: usize,
+    pub style: Style,
+    pub shimmer_style: Style,
+}
+
+impl<I> Skeleton<I> {
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            phase: 0,
+            style: Theme::default().dim,
+            shimmer_style: Style::new().grey(),
+        }
+    }
+
+    pub fn with_phase(mut self, phase: usize) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_shimmer_style(mut self, style: Style) -> Self {
+        self.shimmer_style = style;
+        self
+    }
+
+    pub fn with_theme(self, theme: &Theme) -> Self {
+        self.with_style(theme.dim)
+    }
+
+    fn line_length(width: u16, height: u16, y: u16) -> u16 {
+        let percent = if height > 1 && y == height - 1 {
+            LAST_LINE_LENGTH
+        } else {
+            LINE_LENGTHS[y as usize % LINE_LENGTHS.len()]
+        };
+        let length = (width as u32 * percent as u32 / 100) as u16;
+        length.clamp(1.min(width), width)
+    }
+
+    fn draw_skeleton(&self, frame: &mut Frame) {
+        let size = frame.size();
+        let period = size.width as usize + size.height as usize + SHIMMER_WIDTH as usize;
+        let shimmer_start = (self.phase % period) as i32 - SHIMMER_WIDTH;
+        let shimmer = shimmer_start..shimmer_start + SHIMMER_WIDTH;
+
+        for y in 0..size.height {
+            for x in 0..Self::line_length(size.width, size.height, y) {
+                let style = if shimmer.contains(&(x as i32 + y as i32)) {
+                    self.shimmer_style
+                } else {
+                    self.style
+                };
+                frame.write(Pos::new(x.into(), y.into()), ("▇", style));
+            }
+        }
+    }
+}
+
+impl<E, I> Widget<E> for Skeleton<I>
+where
+    I: Widget<E>,
+{
+    fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        self.inner.size(widthdb, max_width, max_height)
+    }
+
+    fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        self.draw_skeleton(frame);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, I> AsyncWidget<E> for Skeleton<I>
+where
+    I: AsyncWidget<E> + Send + Sync,
+{
+    async fn size(
+        &self,
+        widthdb: &mut WidthDb,
+        max_width: Option<u16>,
+        max_height: Option<u16>,
+    ) -> Result<Size, E> {
+        self.inner.size(widthdb, max_width, max_height).await
+    }
+
+    async fn draw(self, frame: &mut Frame) -> Result<(), E> {
+        self.draw_skeleton(frame);
+        Ok(())
+    }
+}
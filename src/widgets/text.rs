@@ -1,9 +1,28 @@
-use crate::{Frame, Pos, Size, Styled, Widget, WidthDb};
+use std::mem;
+
+use crate::widgets::gutter::{self, LineStarts};
+use crate::{wrap, Alignment, Frame, Pos, Size, Styled, TruncateAt, Widget, WidthDb};
 
 #[derive(Debug, Clone)]
 pub struct Text {
     pub styled: Styled,
     pub wrap: bool,
+    /// Whether lines may be broken between any two graphemes instead of only
+    /// at word boundaries.
+    pub break_anywhere: bool,
+    /// Where to cut lines that are too wide, if they should be truncated
+    /// instead of wrapped.
+    pub truncate: Option<TruncateAt>,
+    /// Amount of spaces in front of the first row of each line.
+    pub first_line_indent: usize,
+    /// Amount of spaces in front of rows continuing a soft-wrapped line.
+    pub continuation_indent: usize,
+    /// How rows are aligned within the available width.
+    pub alignment: Alignment,
+    /// Language whose hyphenation patterns are used to split long words while
+    /// wrapping, if any.
+    #[cfg(feature = "hyphenation")]
+    pub hyphenation: Option<crate::HyphenationLang>,
 }
 
 impl Text {
@@ -11,6 +30,13 @@ impl Text {
         Self {
             styled: styled.into(),
             wrap: true,
+            break_anywhere: false,
+            truncate: None,
+            first_line_indent: 0,
+            continuation_indent: 0,
+            alignment: Alignment::Left,
+            #[cfg(feature = "hyphenation")]
+            hyphenation: None,
         }
     }
 
@@ -19,14 +45,134 @@ impl Text {
         self
     }
 
+    pub fn with_break_anywhere(mut self, active: bool) -> Self {
+        self.break_anywhere = active;
+        self
+    }
+
+    pub fn with_truncate(mut self, at: Option<TruncateAt>) -> Self {
+        self.truncate = at;
+        self
+    }
+
+    pub fn with_first_line_indent(mut self, indent: usize) -> Self {
+        self.first_line_indent = indent;
+        self
+    }
+
+    pub fn with_continuation_indent(mut self, indent: usize) -> Self {
+        self.continuation_indent = indent;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    #[cfg(feature = "hyphenation")]
+    pub fn with_hyphenation(mut self, lang: Option<crate::HyphenationLang>) -> Self {
+        self.hyphenation = lang;
+        self
+    }
+
     fn wrapped(&self, widthdb: &mut WidthDb, max_width: Option<u16>) -> Vec<Styled> {
+        let mut lines = self.unindented(widthdb, max_width);
+        let starts = gutter::row_line_starts(&lines);
+        self.indent(&mut lines, &starts);
+        lines
+    }
+
+    /// Like [`Self::wrapped`], but the rows are also aligned within the width.
+    fn aligned(&self, widthdb: &mut WidthDb, width: u16) -> Vec<Styled> {
+        let mut lines = self.unindented(widthdb, Some(width));
+        let starts = gutter::row_line_starts(&lines);
+        if self.alignment != Alignment::Left {
+            let ends = starts.iter().skip(1).copied().chain([true]);
+            lines = lines
+                .into_iter()
+                .zip(&starts)
+                .zip(ends)
+                .map(|((line, start), ends_line)| {
+                    let width = (width as usize).saturating_sub(self.indent_width(*start));
+                    wrap::align_row(widthdb, line, width, self.alignment, ends_line)
+                })
+                .collect();
+        }
+        self.indent(&mut lines, &starts);
+        lines
+    }
+
+    fn indent_width(&self, line_start: bool) -> usize {
+        if line_start {
+            self.first_line_indent
+        } else {
+            self.continuation_indent
+        }
+    }
+
+    fn indent(&self, lines: &mut [Styled], starts: &[bool]) {
+        if self.first_line_indent == 0 && self.continuation_indent == 0 {
+            return;
+        }
+        for (line, start) in lines.iter_mut().zip(starts) {
+            let indent = " ".repeat(self.indent_width(*start));
+            *line = Styled::new_plain(indent).and_then(mem::take(line));
+        }
+    }
+
+    fn unindented(&self, widthdb: &mut WidthDb, max_width: Option<u16>) -> Vec<Styled> {
+        if let Some(at) = self.truncate {
+            let max_width = max_width
+                .map(|w| w as usize)
+                .unwrap_or(usize::MAX)
+                .saturating_sub(self.first_line_indent);
+            let indices = widthdb.wrap(self.styled.text(), usize::MAX);
+            return self
+                .styled
+                .clone()
+                .split_at_indices(&indices)
+                .into_iter()
+                .map(|line| {
+                    let len = line.text().trim_end_matches(['\r', '\n']).len();
+                    let (line, newline) = line.split_at(len);
+                    line.truncate_to_width(widthdb, max_width, at)
+                        .and_then(newline)
+                })
+                .collect();
+        }
+
         let max_width = max_width
             .filter(|_| self.wrap)
             .map(|w| w as usize)
             .unwrap_or(usize::MAX);
 
-        let indices = widthdb.wrap(self.styled.text(), max_width);
-        self.styled.clone().split_at_indices(&indices)
+        let text = self.styled.text();
+        let mut extra_breaks = vec![];
+        #[cfg(feature = "hyphenation")]
+        if let Some(lang) = self.hyphenation {
+            extra_breaks = wrap::hyphen_breaks(text, lang);
+        }
+        if self.break_anywhere {
+            extra_breaks = wrap::grapheme_breaks(text);
+        }
+
+        let breaks = wrap::wrap_with_breaks(
+            widthdb,
+            text,
+            max_width,
+            self.first_line_indent,
+            self.continuation_indent,
+            &extra_breaks,
+        );
+        let indices = breaks.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+        let mut lines = self.styled.clone().split_at_indices(&indices);
+        for (line, (_, hyphen)) in lines.iter_mut().zip(breaks) {
+            if hyphen {
+                line.push_plain("-");
+            }
+        }
+        lines
     }
 }
 
@@ -41,7 +187,7 @@ impl<E> Widget<E> for Text {
 
         let min_width = lines
             .iter()
-            .map(|l| widthdb.width(l.text().trim_end()))
+            .map(|l| l.width_trim_end(widthdb))
             .max()
             .unwrap_or(0);
         let min_height = lines.len();
@@ -55,7 +201,7 @@ impl<E> Widget<E> for Text {
         let size = frame.size();
 
         for (i, line) in self
-            .wrapped(frame.widthdb(), Some(size.width))
+            .aligned(frame.widthdb(), size.width)
             .into_iter()
             .enumerate()
         {
@@ -66,3 +212,9 @@ impl<E> Widget<E> for Text {
         Ok(())
     }
 }
+
+impl LineStarts for Text {
+    fn line_starts(&self, widthdb: &mut WidthDb, max_width: Option<u16>) -> Vec<bool> {
+        gutter::row_line_starts(&self.wrapped(widthdb, max_width))
+    }
+}
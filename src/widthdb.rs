@@ -1,22 +1,126 @@
 use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
 use std::io::{self, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crossterm::cursor::MoveTo;
-use crossterm::style::Print;
-use crossterm::terminal::{Clear, ClearType};
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use crossterm::terminal::{BeginSynchronizedUpdate, Clear, ClearType, EndSynchronizedUpdate};
 use crossterm::QueueableCommand;
 use unicode_segmentation::UnicodeSegmentation;
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::wrap;
+use crate::{query, wrap, TruncateAt};
+
+/// Maximum amount of memoized string widths. Once exceeded, all memoized widths
+/// are forgotten.
+const MEMO_CAPACITY: usize = 4096;
+
+/// First line of files created by [`WidthDb::save`].
+const CACHE_HEADER: &str = "toss grapheme widths v1";
+
+/// Generations are unique across all [`WidthDb`]s so that values cached for
+/// one instance are never mistaken as valid for another.
+fn next_generation() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// How the width of graphemes is estimated when it is not known.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WidthEstimationMethod {
+    /// Sum of the widths of the grapheme's individual code points.
+    ///
+    /// Most terminals measure text this way, so for example an emoji consisting
+    /// of multiple emoji joined by zero width joiners is as wide as all of its
+    /// parts together.
+    #[default]
+    Legacy,
+    /// Width of the grapheme as a whole according to the Unicode Standard Annex
+    /// #11.
+    ///
+    /// Matches terminals that treat grapheme clusters as a unit, also known as
+    /// mode 2027.
+    Unicode,
+}
+
+impl WidthEstimationMethod {
+    /// Determine which method matches the terminal best.
+    ///
+    /// Asks the terminal whether it supports mode 2027 by writing a query to
+    /// `out`, which must be connected to the terminal. The reply is read from
+    /// the controlling terminal. If the terminal doesn't reply in time, the
+    /// method is chosen based on the `TERM_PROGRAM` and `TERM` environment
+    /// variables of terminals known to support mode 2027.
+    pub fn detect<W: Write + ?Sized>(out: &mut W) -> Self {
+        match query::grapheme_cluster_mode_supported(out) {
+            Ok(Some(true)) => Self::Unicode,
+            Ok(Some(false)) => Self::Legacy,
+            Ok(None) | Err(_) => Self::detect_from_env(),
+        }
+    }
+
+    pub(crate) fn detect_from_env() -> Self {
+        let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        let supported = ["WezTerm", "ghostty", "contour"].contains(&term_program.as_str())
+            || term.starts_with("xterm-ghostty")
+            || term.starts_with("contour")
+            || term.starts_with("foot");
+        if supported {
+            Self::Unicode
+        } else {
+            Self::Legacy
+        }
+    }
+
+    fn estimate(self, grapheme: &str) -> u8 {
+        let width: usize = match self {
+            Self::Legacy => grapheme.chars().filter_map(|c| c.width()).sum(),
+            Self::Unicode => grapheme.width(),
+        };
+        width.try_into().unwrap_or(u8::MAX)
+    }
+}
+
+/// A grapheme width that is known, along with when it was last used.
+#[derive(Debug, Clone, Copy)]
+struct KnownWidth {
+    width: u8,
+    last_used: u64,
+}
+
+/// A memoized string width, along with when it was last used.
+#[derive(Debug, Clone, Copy)]
+struct MemoizedWidth {
+    width: usize,
+    last_used: u64,
+}
 
 /// Measures and stores the with (in terminal coordinates) of graphemes.
 #[derive(Debug)]
 pub struct WidthDb {
     pub(crate) active: bool,
     pub(crate) tab_width: u8,
-    known: HashMap<String, u8>,
+    estimation_method: WidthEstimationMethod,
+    known: HashMap<String, KnownWidth>,
+    /// Maximum amount of known widths.
+    capacity: Option<usize>,
+    /// Incremented whenever a known width is used.
+    clock: u64,
+    /// Value of the clock when the current frame started.
+    frame_start: u64,
+    /// Value of the clock when the previous frame started. Widths used since
+    /// then are never evicted.
+    prev_frame_start: u64,
     requested: HashSet<String>,
+    /// Changes whenever widths returned previously may have changed.
+    generation: u64,
+    /// Widths of entire strings, if memoization is enabled. Only valid for the
+    /// generation they were computed in.
+    memo: Option<(u64, HashMap<String, MemoizedWidth>)>,
 }
 
 impl Default for WidthDb {
@@ -24,8 +128,15 @@ impl Default for WidthDb {
         Self {
             active: false,
             tab_width: 8,
+            estimation_method: WidthEstimationMethod::default(),
             known: Default::default(),
+            capacity: None,
+            clock: 0,
+            frame_start: 0,
+            prev_frame_start: 0,
             requested: Default::default(),
+            generation: next_generation(),
+            memo: None,
         }
     }
 }
@@ -40,31 +151,61 @@ impl WidthDb {
     ///
     /// If the grapheme is a tab, the column is used to determine its width.
     ///
-    /// If the width has not been measured yet or measurements are turned off,
-    /// it is estimated using the Unicode Standard Annex #11.
+    /// If the width is not known (see [`Self::known`]), it is estimated using
+    /// the [`Self::estimation_method`].
     pub fn grapheme_width(&mut self, grapheme: &str, col: usize) -> u8 {
         assert_eq!(Some(grapheme), grapheme.graphemes(true).next());
         if grapheme == "\t" {
             return self.tab_width_at_column(col);
         }
-        if !self.active {
-            return grapheme.width() as u8;
+        if let Some(known) = self.known.get_mut(grapheme) {
+            self.clock += 1;
+            known.last_used = self.clock;
+            return known.width;
         }
-        if let Some(width) = self.known.get(grapheme) {
-            *width
-        } else {
+        if self.active {
             self.requested.insert(grapheme.to_string());
-            grapheme.width() as u8
         }
+        self.estimation_method.estimate(grapheme)
     }
 
     /// Determine the width of a string based on its graphemes.
     ///
     /// If a grapheme is a tab, its column is used to determine its width.
     ///
-    /// If the width of a grapheme has not been measured yet or measurements are
-    /// turned off, it is estimated using the Unicode Standard Annex #11.
+    /// If the width of a grapheme is not known (see [`Self::known`]), it is
+    /// estimated using the [`Self::estimation_method`].
+    ///
+    /// If memoization is enabled (see [`Self::set_memoize_widths`]), results
+    /// are cached.
     pub fn width(&mut self, s: &str) -> usize {
+        let Some((generation, memo)) = &mut self.memo else {
+            return self.compute_width(s);
+        };
+
+        if *generation != self.generation {
+            *generation = self.generation;
+            memo.clear();
+        }
+
+        if let Some(memoized) = memo.get_mut(s) {
+            self.clock += 1;
+            memoized.last_used = self.clock;
+            return memoized.width;
+        }
+
+        let width = self.compute_width(s);
+        if let Some((_, memo)) = &mut self.memo {
+            if memo.len() >= MEMO_CAPACITY {
+                memo.clear();
+            }
+            let last_used = self.clock;
+            memo.insert(s.to_string(), MemoizedWidth { width, last_used });
+        }
+        width
+    }
+
+    fn compute_width(&mut self, s: &str) -> usize {
         let mut total: usize = 0;
         for grapheme in s.graphemes(true) {
             total += self.grapheme_width(grapheme, total) as usize;
@@ -72,6 +213,25 @@ impl WidthDb {
         total
     }
 
+    /// Enable or disable memoizing the results of [`Self::width`].
+    ///
+    /// For more details, see [`Self::memoize_widths`].
+    pub fn set_memoize_widths(&mut self, active: bool) {
+        if active != self.memo.is_some() {
+            self.memo = active.then(|| (self.generation, HashMap::new()));
+        }
+    }
+
+    /// Whether the results of [`Self::width`] are cached.
+    ///
+    /// Widgets like status bars, lists or editors often measure the same
+    /// unchanged strings every frame. With memoization, the width of a string
+    /// is only computed again if it may have changed, for example because new
+    /// graphemes were measured. Disabled by default.
+    pub fn memoize_widths(&self) -> bool {
+        self.memo.is_some()
+    }
+
     /// Perform primitive word wrapping with the specified maximum width.
     ///
     /// Returns the byte offsets at which the string should be split into lines.
@@ -84,39 +244,448 @@ impl WidthDb {
         wrap::wrap(self, text, width)
     }
 
+    /// Like [`Self::wrap`], but lines may be broken between any two graphemes.
+    ///
+    /// Useful for text without natural break opportunities, like URLs, hashes
+    /// or log output.
+    pub fn wrap_anywhere(&mut self, text: &str, width: usize) -> Vec<usize> {
+        wrap::wrap_anywhere(self, text, width)
+    }
+
+    /// Cut the text to at most the given width, replacing the removed part by
+    /// an ellipsis.
+    ///
+    /// See [`Styled::truncate_to_width`](crate::Styled::truncate_to_width) for
+    /// details.
+    pub fn truncate(&mut self, text: &str, width: usize, at: TruncateAt) -> String {
+        wrap::truncate(self, text, width, at)
+    }
+
+    /// Like [`Self::wrap`], but long words may also be split at syllable
+    /// boundaries using the hyphenation patterns of the specified language.
+    ///
+    /// Returns the byte offsets at which the string should be split into lines,
+    /// along with whether the line ending at the offset should be displayed
+    /// with a trailing hyphen.
+    #[cfg(feature = "hyphenation")]
+    pub fn wrap_hyphenated(
+        &mut self,
+        text: &str,
+        width: usize,
+        lang: crate::HyphenationLang,
+    ) -> Vec<(usize, bool)> {
+        wrap::wrap_hyphenated(self, text, width, lang)
+    }
+
+    /// Like [`Self::wrap`], but the first row of each line has
+    /// `first_line_indent` fewer columns available and rows continuing a
+    /// soft-wrapped line have `continuation_indent` fewer columns available.
+    ///
+    /// The indentation itself is not part of the text and must be added when
+    /// rendering the rows.
+    pub fn wrap_indented(
+        &mut self,
+        text: &str,
+        width: usize,
+        first_line_indent: usize,
+        continuation_indent: usize,
+    ) -> Vec<usize> {
+        wrap::wrap_indented(self, text, width, first_line_indent, continuation_indent)
+    }
+
+    /// How the width of graphemes is estimated when it is not known.
+    pub fn estimation_method(&self) -> WidthEstimationMethod {
+        self.estimation_method
+    }
+
+    pub(crate) fn set_estimation_method(&mut self, method: WidthEstimationMethod) {
+        if method != self.estimation_method {
+            self.estimation_method = method;
+            self.invalidate();
+        }
+    }
+
+    /// Create a copy of this database that can be moved to another thread, for
+    /// example to compute wrapped layouts in the background without access to
+    /// the [`crate::Frame`].
+    ///
+    /// The snapshot returns the same widths as this database until either of
+    /// them changes, so values computed using the snapshot remain valid for
+    /// this database in the meantime. Graphemes the snapshot has not seen
+    /// before are estimated, but not measured. To measure them, pass the
+    /// snapshot back to [`Self::merge_requests`] once it is no longer needed.
+    pub fn snapshot(&self) -> Self {
+        let memo = self
+            .memoize_widths()
+            .then(|| (self.generation, HashMap::new()));
+        Self {
+            active: self.active,
+            tab_width: self.tab_width,
+            estimation_method: self.estimation_method,
+            known: self.known.clone(),
+            capacity: self.capacity,
+            clock: self.clock,
+            frame_start: self.frame_start,
+            prev_frame_start: self.prev_frame_start,
+            requested: HashSet::new(),
+            generation: self.generation,
+            memo,
+        }
+    }
+
+    /// Request measuring all graphemes that a snapshot created using
+    /// [`Self::snapshot`] has seen but could not measure.
+    pub fn merge_requests(&mut self, snapshot: Self) {
+        if !self.active {
+            return;
+        }
+        for grapheme in snapshot.requested {
+            if !self.known.contains_key(&grapheme) {
+                self.requested.insert(grapheme);
+            }
+        }
+    }
+
+    /// The widths of all graphemes that have been measured, loaded or inserted
+    /// using [`Self::insert_known`].
+    ///
+    /// Known widths are used even if measuring is turned off.
+    pub fn known(&self) -> impl Iterator<Item = (&str, u8)> + '_ {
+        self.known.iter().map(|(g, k)| (g as &str, k.width))
+    }
+
+    /// Set the width of a grapheme, overriding any measured or estimated
+    /// width.
+    ///
+    /// This can be used to pre-seed graphemes whose width is known to be
+    /// estimated incorrectly, or to share measurements between processes.
+    pub fn insert_known<S: Into<String>>(&mut self, grapheme: S, width: u8) {
+        let grapheme = grapheme.into();
+        self.requested.remove(&grapheme);
+        self.insert(grapheme, width);
+        self.invalidate();
+        self.evict();
+    }
+
+    /// Forget all known widths.
+    ///
+    /// Graphemes need to be measured again afterwards.
+    pub fn clear(&mut self) {
+        self.known.clear();
+        self.requested.clear();
+        self.invalidate();
+    }
+
+    /// The maximum amount of known widths, if any.
+    ///
+    /// For more details, see [`Self::set_capacity`].
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Limit the amount of known widths.
+    ///
+    /// Applications displaying arbitrary text for a long time may encounter an
+    /// unbounded amount of different graphemes. With a capacity, the least
+    /// recently used widths are forgotten once the capacity is exceeded. To
+    /// avoid doing this for every new grapheme, a quarter of the widths are
+    /// forgotten at once. Forgotten widths are measured again when needed.
+    ///
+    /// Widths used in the current or previous frame are never forgotten, so
+    /// the capacity may be exceeded while a frame displays more different
+    /// graphemes than that.
+    ///
+    /// There is no limit by default.
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    fn insert(&mut self, grapheme: String, width: u8) {
+        self.clock += 1;
+        let known = KnownWidth {
+            width,
+            last_used: self.clock,
+        };
+        self.known.insert(grapheme, known);
+    }
+
+    /// Forget the least recently used widths if there are more than allowed.
+    fn evict(&mut self) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        if self.known.len() <= capacity {
+            return;
+        }
+
+        // Memoized widths are used without looking up their graphemes
+        if let Some((_, memo)) = &self.memo {
+            for (s, memoized) in memo {
+                if memoized.last_used <= self.prev_frame_start {
+                    continue;
+                }
+                for grapheme in s.graphemes(true) {
+                    if let Some(known) = self.known.get_mut(grapheme) {
+                        known.last_used = known.last_used.max(memoized.last_used);
+                    }
+                }
+            }
+        }
+
+        // Evicting widths the frame is using would cause them to be measured
+        // again every time the frame is redrawn
+        let mut last_used = self
+            .known
+            .values()
+            .map(|k| k.last_used)
+            .filter(|t| *t <= self.prev_frame_start)
+            .collect::<Vec<_>>();
+        let target = capacity - capacity / 4;
+        let remove = (self.known.len() - target).min(last_used.len());
+        if remove == 0 {
+            return;
+        }
+        let (_, threshold, _) = last_used.select_nth_unstable(remove - 1);
+        let threshold = *threshold;
+        self.known.retain(|_, k| k.last_used > threshold);
+        self.invalidate();
+    }
+
+    /// Save all known grapheme widths to a file, so they can be loaded again
+    /// using [`Self::load`] the next time the program runs.
+    ///
+    /// This way, graphemes only need to be measured once per terminal instead
+    /// of every time the program is started.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut contents = String::from(CACHE_HEADER);
+        contents.push('\n');
+        for (grapheme, width) in self.known() {
+            contents.push_str(&width.to_string());
+            for c in grapheme.chars() {
+                contents.push_str(&format!(" {:x}", c as u32));
+            }
+            contents.push('\n');
+        }
+
+        // Write to a temporary file first so other processes never see a
+        // partially written file
+        let path = path.as_ref();
+        let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp_path, contents)?;
+        fs::rename(tmp_path, path)
+    }
+
+    /// Load grapheme widths from a file created using [`Self::save`].
+    ///
+    /// Loaded widths replace widths that are already known. Lines that can't
+    /// be parsed are ignored.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(CACHE_HEADER) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a grapheme width file",
+            ));
+        }
+
+        for line in lines {
+            let mut parts = line.split(' ');
+            let Some(Ok(width)) = parts.next().map(|w| w.parse::<u8>()) else {
+                continue;
+            };
+            let grapheme = parts
+                .map(|c| u32::from_str_radix(c, 16).ok().and_then(char::from_u32))
+                .collect::<Option<String>>();
+            if let Some(grapheme) = grapheme.filter(|g| !g.is_empty()) {
+                self.requested.remove(&grapheme);
+                self.insert(grapheme, width);
+            }
+        }
+
+        self.invalidate();
+        self.evict();
+        Ok(())
+    }
+
+    /// The default file for caching grapheme widths, see
+    /// [`Self::load_cache`].
+    ///
+    /// Widths depend on the terminal and its font, so the file name is based on
+    /// the `TERM` and `TERM_PROGRAM` environment variables. The file lives in
+    /// `$XDG_CACHE_HOME/toss` or `~/.cache/toss` (`%LOCALAPPDATA%\toss` on
+    /// Windows). Returns [`None`] if no cache directory could be found.
+    pub fn cache_path() -> Option<PathBuf> {
+        #[cfg(not(windows))]
+        let dir = env::var_os("XDG_CACHE_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+        #[cfg(windows)]
+        let dir = PathBuf::from(env::var_os("LOCALAPPDATA")?);
+
+        let mut name = String::from("widths");
+        for var in ["TERM", "TERM_PROGRAM"] {
+            if let Ok(value) = env::var(var) {
+                let value = value.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+                name.push('-');
+                name.push_str(&value);
+            }
+        }
+        name.push_str(".txt");
+
+        Some(dir.join("toss").join(name))
+    }
+
+    /// Load grapheme widths from [`Self::cache_path`], if the file exists.
+    pub fn load_cache(&mut self) -> io::Result<()> {
+        let Some(path) = Self::cache_path() else {
+            return Ok(());
+        };
+        match self.load(path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Save all known grapheme widths to [`Self::cache_path`], creating the
+    /// directory if necessary.
+    ///
+    /// Does nothing if no widths are known, for example because measuring is
+    /// disabled.
+    pub fn save_cache(&self) -> io::Result<()> {
+        let Some(path) = Self::cache_path() else {
+            return Ok(());
+        };
+        if self.known.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        self.save(path)
+    }
+
+    /// A value that changes whenever widths returned previously may have
+    /// changed, for example because new widths were measured.
+    ///
+    /// Can be used to invalidate caches containing widths or wrapping
+    /// information.
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Remember which widths were used in the previous frame so they are not
+    /// evicted while the next frame is drawn and measured.
+    pub(crate) fn start_frame(&mut self) {
+        self.prev_frame_start = self.frame_start;
+        self.frame_start = self.clock;
+    }
+
+    /// Mark all widths returned previously as potentially outdated.
+    pub(crate) fn invalidate(&mut self) {
+        self.generation = next_generation();
+    }
+
     /// Whether any new graphemes have been seen since the last time
     /// [`Self::measure_widths`] was called.
     pub(crate) fn measuring_required(&self) -> bool {
         self.active && !self.requested.is_empty()
     }
 
+    /// Forget all new graphemes that have been seen since the last time
+    /// [`Self::measure_widths`] was called, for when they can't be measured.
+    pub(crate) fn discard_requests(&mut self) {
+        self.requested.clear();
+    }
+
     /// Measure the width of all new graphemes that have been seen since the
     /// last time this function was called.
     ///
     /// This function measures the actual width of graphemes by writing them to
-    /// the terminal. After it finishes, the terminal's contents should be
-    /// assumed to be garbage and a full redraw should be performed.
-    pub(crate) fn measure_widths(&mut self, out: &mut impl Write) -> io::Result<()> {
-        if !self.active {
+    /// the specified row of the terminal. Afterwards, the row is cleared and the
+    /// cursor is moved back to where it was. The rest of the screen is left
+    /// intact.
+    ///
+    /// The measurements happen during a synchronized update, so terminals
+    /// supporting it never display the graphemes being measured.
+    ///
+    /// Querying the cursor position requires a round trip to the terminal,
+    /// which is slow over high-latency connections. To avoid one round trip
+    /// per grapheme, graphemes are measured in groups that fit into the row.
+    /// The cursor position is queried after each grapheme of a group, but all
+    /// queries are sent at once. Graphemes are separated by spaces so
+    /// neighbouring graphemes can't combine with each other.
+    pub(crate) fn measure_widths(
+        &mut self,
+        out: &mut impl Write,
+        row: u16,
+        row_width: u16,
+    ) -> io::Result<()> {
+        if !self.active || self.requested.is_empty() {
             return Ok(());
         }
-        for grapheme in self.requested.drain() {
+        self.invalidate();
+
+        let mut groups = vec![];
+        let mut group = vec![];
+        let mut group_width = 0;
+        for grapheme in mem::take(&mut self.requested) {
             if grapheme.chars().any(|c|c.is_ascii_control()){
                 // ASCII control characters like the escape character or the
                 // bell character tend to be interpreted specially by terminals.
                 // This may break width measurements. To avoid this, we just
                 // assign each control character a with of 0.
-                self.known.insert(grapheme, 0);
+                self.insert(grapheme, 0);
                 continue;
             }
 
-            out.queue(Clear(ClearType::All))?
-                .queue(MoveTo(0, 0))?
-                .queue(Print(&grapheme))?;
-            out.flush()?;
-            let width = crossterm::cursor::position()?.0 as u8;
-            self.known.insert(grapheme, width);
+            // Groups must fit into the row without wrapping, even if the
+            // graphemes are a bit wider than estimated.
+            let width = usize::from(self.estimation_method.estimate(&grapheme)) + 1;
+            if !group.is_empty() && group_width + width >= row_width.into() {
+                groups.push(mem::take(&mut group));
+                group_width = 0;
+            }
+            group.push(grapheme);
+            group_width += width;
         }
+        if !group.is_empty() {
+            groups.push(group);
+        }
+
+        out.queue(BeginSynchronizedUpdate)?.queue(SavePosition)?;
+        while let Some(mut group) = groups.pop() {
+            out.queue(MoveTo(0, row))?
+                .queue(Clear(ClearType::CurrentLine))?;
+            let texts = group
+                .iter()
+                .enumerate()
+                .map(|(i, g)| if i == 0 { g.clone() } else { format!(" {g}") })
+                .collect::<Vec<_>>();
+            let positions = query::cursor_positions(out, &texts)?;
+
+            let mut start = 0;
+            for (i, (x, y)) in positions.into_iter().enumerate() {
+                if i > 0 && y != row {
+                    // A grapheme was wider than estimated and the group
+                    // wrapped, so the remaining graphemes are measured again
+                    groups.push(group.split_off(i));
+                    break;
+                }
+                let width = x.saturating_sub(start);
+                start = x + 1;
+                self.insert(group[i].clone(), width as u8);
+            }
+        }
+        out.queue(MoveTo(0, row))?
+            .queue(Clear(ClearType::CurrentLine))?
+            .queue(RestorePosition)?
+            .queue(EndSynchronizedUpdate)?;
+        out.flush()?;
+
+        self.evict();
         Ok(())
     }
 }
@@ -3,14 +3,175 @@
 use unicode_linebreak::BreakOpportunity;
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::WidthDb;
+use crate::{Alignment, Style, Styled, TruncateAt, WidthDb};
 
 pub fn wrap(widthdb: &mut WidthDb, text: &str, width: usize) -> Vec<usize> {
+    wrap_indented(widthdb, text, width, 0, 0)
+}
+
+/// Like [`wrap`], but the first row of each line has `first_line_indent` fewer
+/// columns available and rows continuing a soft-wrapped line have
+/// `continuation_indent` fewer columns available.
+pub fn wrap_indented(
+    widthdb: &mut WidthDb,
+    text: &str,
+    width: usize,
+    first_line_indent: usize,
+    continuation_indent: usize,
+) -> Vec<usize> {
+    wrap_with_breaks(
+        widthdb,
+        text,
+        width,
+        first_line_indent,
+        continuation_indent,
+        &[],
+    )
+    .into_iter()
+    .map(|(i, _)| i)
+    .collect()
+}
+
+/// Cut the text to at most the given width, replacing the removed part by an
+/// ellipsis.
+///
+/// See [`Styled::truncate_to_width`] for details.
+pub fn truncate(widthdb: &mut WidthDb, text: &str, width: usize, at: TruncateAt) -> String {
+    Styled::new_plain(text)
+        .truncate_to_width(widthdb, width, at)
+        .text()
+        .to_string()
+}
+
+/// Align rows produced by wrapping to the given width.
+///
+/// Trailing whitespace, including newlines, is removed from the rows. When
+/// justifying, the last row of each line is aligned left.
+pub(crate) fn align_rows(
+    widthdb: &mut WidthDb,
+    rows: Vec<Styled>,
+    width: usize,
+    alignment: Alignment,
+) -> Vec<Styled> {
+    let last = rows.len().saturating_sub(1);
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let ends_line = i == last || row.text().ends_with('\n');
+            align_row(widthdb, row, width, alignment, ends_line)
+        })
+        .collect()
+}
+
+/// Align a single row produced by wrapping to the given width, removing its
+/// trailing whitespace.
+///
+/// Rows ending a line are not justified.
+pub(crate) fn align_row(
+    widthdb: &mut WidthDb,
+    mut row: Styled,
+    width: usize,
+    alignment: Alignment,
+    ends_line: bool,
+) -> Styled {
+    let alignment = match alignment {
+        Alignment::Justify if ends_line => Alignment::Left,
+        alignment => alignment,
+    };
+    row.trim_end();
+    row.pad_to_width(widthdb, width, alignment, Style::new())
+}
+
+pub fn wrap_anywhere(widthdb: &mut WidthDb, text: &str, width: usize) -> Vec<usize> {
+    wrap_with_breaks(widthdb, text, width, 0, 0, &grapheme_breaks(text))
+        .into_iter()
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Extra break options between any two graphemes, for use with
+/// [`wrap_with_breaks`].
+pub(crate) fn grapheme_breaks(text: &str) -> Vec<(usize, bool)> {
+    text.grapheme_indices(true)
+        .skip(1)
+        .map(|(gi, _)| (gi, false))
+        .collect()
+}
+
+/// A language whose hyphenation patterns are used to split words into
+/// syllables while wrapping.
+#[cfg(feature = "hyphenation")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HyphenationLang(hypher::Lang);
+
+#[cfg(feature = "hyphenation")]
+impl HyphenationLang {
+    /// Select a language using its ISO 639-1 code, for example `"en"`.
+    ///
+    /// Returns [`None`] if no hyphenation patterns exist for the language.
+    pub fn from_iso(code: &str) -> Option<Self> {
+        let code = code.as_bytes().try_into().ok()?;
+        hypher::Lang::from_iso(code).map(Self)
+    }
+}
+
+/// Like [`wrap`], but words may additionally be broken at syllable
+/// boundaries.
+///
+/// Returns the indices of the breaks along with whether the row ending at the
+/// break should end in a hyphen.
+#[cfg(feature = "hyphenation")]
+pub fn wrap_hyphenated(
+    widthdb: &mut WidthDb,
+    text: &str,
+    width: usize,
+    lang: HyphenationLang,
+) -> Vec<(usize, bool)> {
+    wrap_with_breaks(widthdb, text, width, 0, 0, &hyphen_breaks(text, lang))
+}
+
+/// Extra break options at the syllable boundaries of words, for use with
+/// [`wrap_with_breaks`].
+#[cfg(feature = "hyphenation")]
+pub(crate) fn hyphen_breaks(text: &str, lang: HyphenationLang) -> Vec<(usize, bool)> {
+    let mut hyphens = vec![];
+    for (wi, word) in text.split_word_bound_indices() {
+        if !word.chars().all(char::is_alphabetic) {
+            continue;
+        }
+        let mut offset = wi;
+        let mut syllables = hypher::hyphenate(word, lang.0).peekable();
+        while let Some(syllable) = syllables.next() {
+            offset += syllable.len();
+            if syllables.peek().is_some() {
+                hyphens.push((offset, true));
+            }
+        }
+    }
+
+    hyphens
+}
+
+/// Wrap text, additionally allowing breaks at the specified indices, which
+/// must be sorted. Breaks requiring a hyphen are only allowed if the row has
+/// enough space left for the hyphen.
+///
+/// Returns the indices of the breaks along with whether the row ending at the
+/// break should end in a hyphen.
+pub(crate) fn wrap_with_breaks(
+    widthdb: &mut WidthDb,
+    text: &str,
+    width: usize,
+    first_line_indent: usize,
+    continuation_indent: usize,
+    extra_breaks: &[(usize, bool)],
+) -> Vec<(usize, bool)> {
     let mut breaks = vec![];
 
     let mut break_options = unicode_linebreak::linebreaks(text).peekable();
+    let mut extra_breaks = extra_breaks.iter().copied().peekable();
 
-    // The last valid break point encountered and its width
+    // The last valid break point encountered and whether it requires a hyphen
     let mut valid_break = None;
 
     // Starting index and width of the line at the current grapheme (with and
@@ -19,6 +180,9 @@ pub fn wrap(widthdb: &mut WidthDb, text: &str, width: usize) -> Vec<usize> {
     let mut current_width = 0;
     let mut current_width_trimmed = 0;
 
+    // Whether the current line continues a soft-wrapped line
+    let mut continued = false;
+
     for (gi, g) in text.grapheme_indices(true) {
         // Advance break options
         let (bi, b) = loop {
@@ -30,18 +194,36 @@ pub fn wrap(widthdb: &mut WidthDb, text: &str, width: usize) -> Vec<usize> {
             }
         };
 
+        // Wrapping limit for the current line
+        let limit = if continued {
+            width.saturating_sub(continuation_indent)
+        } else {
+            width.saturating_sub(first_line_indent)
+        };
+
+        // Evaluate extra break options at the current position, unless there
+        // is a regular break option or the row would be empty. The row must
+        // have room for a hyphen.
+        while extra_breaks.next_if(|(ei, _)| *ei < gi).is_some() {}
+        if let Some((_, hyphen)) = extra_breaks.next_if(|(ei, _)| *ei == gi) {
+            if bi != gi && gi != current_start && (!hyphen || current_width < limit) {
+                valid_break = Some((gi, hyphen));
+            }
+        }
+
         // Evaluate break options at the current position
         if bi == gi {
             match b {
                 BreakOpportunity::Mandatory => {
-                    breaks.push(bi);
+                    breaks.push((bi, false));
                     valid_break = None;
                     current_start = bi;
                     current_width = 0;
                     current_width_trimmed = 0;
+                    continued = false;
                 }
                 BreakOpportunity::Allowed => {
-                    valid_break = Some(bi);
+                    valid_break = Some((bi, false));
                 }
             }
         }
@@ -55,20 +237,26 @@ pub fn wrap(widthdb: &mut WidthDb, text: &str, width: usize) -> Vec<usize> {
         }
 
         // Wrap at last break point if necessary
-        if current_width_trimmed > width {
-            if let Some(bi) = valid_break {
+        if current_width_trimmed > limit {
+            if let Some((bi, hyphen)) = valid_break {
                 let new_line = &text[bi..gi + g.len()];
 
-                breaks.push(bi);
+                breaks.push((bi, hyphen));
                 valid_break = None;
                 current_start = bi;
                 current_width = widthdb.width(new_line);
                 current_width_trimmed = widthdb.width(new_line.trim_end());
+                continued = true;
             }
         }
 
         // Perform a forced break if still necessary
-        if current_width_trimmed > width {
+        let limit = if continued {
+            width.saturating_sub(continuation_indent)
+        } else {
+            width.saturating_sub(first_line_indent)
+        };
+        if current_width_trimmed > limit {
             if current_start == gi {
                 // The grapheme is the only thing on the current line and it is
                 // wider than the maximum width, so we'll allow it, thereby
@@ -78,11 +266,12 @@ pub fn wrap(widthdb: &mut WidthDb, text: &str, width: usize) -> Vec<usize> {
             } else {
                 // Forced break in the middle of a normally non-breakable chunk
                 // because there are no valid break points.
-                breaks.push(gi);
+                breaks.push((gi, false));
                 valid_break = None;
                 current_start = gi;
                 current_width = widthdb.grapheme_width(g, 0).into();
                 current_width_trimmed = if g_is_whitespace { 0 } else { current_width };
+                continued = true;
             }
         }
     }